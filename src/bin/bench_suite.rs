@@ -0,0 +1,71 @@
+// Fixed-workload throughput measurements for comparing performance across
+// commits: the CPU inner loop over a fixed number of salts and, when a device
+// is given, the OpenCL kernel over a fixed amount of time.
+use alloy_primitives::hex;
+use create2crunch::{gpu_hash_rate, Config, Preimage};
+use rayon::prelude::*;
+use std::env;
+use std::time::{Duration, Instant};
+
+// a fixed, arbitrary search (factory, caller and init code hash) with
+// thresholds that will practically never be met
+const FACTORY: &str = "0000000000ffe8b47b3e2130213b802212439497";
+const CALLER: &str = "0000000000000000000000000000000000000000";
+const INIT_CODE_HASH: &str = "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
+
+const CPU_SALTS: u64 = 1 << 24;
+const GPU_DURATION: Duration = Duration::from_secs(10);
+
+fn main() {
+    // usage: bench_suite [device]
+    let device = env::args().nth(1);
+
+    let cpu_rate = cpu_hash_rate();
+    println!(
+        "cpu: {:.2} million hashes per second ({CPU_SALTS} salts)",
+        cpu_rate / 1e6
+    );
+
+    let Some(device) = device else {
+        println!("gpu: skipped (pass a device to measure the OpenCL kernel)");
+        return;
+    };
+    let args = [
+        "bench_suite",
+        FACTORY,
+        CALLER,
+        INIT_CODE_HASH,
+        &device,
+        "20",
+        "255",
+    ];
+    let config = Config::new(args.into_iter().map(String::from)).expect("valid benchmark config");
+    match gpu_hash_rate(&config, GPU_DURATION) {
+        Ok(rate) => println!(
+            "gpu: {:.2} million hashes per second ({}s)",
+            rate / 1e6,
+            GPU_DURATION.as_secs()
+        ),
+        Err(e) => println!("gpu: unavailable ({e})"),
+    }
+}
+
+/// Hash a fixed number of salts with the CPU search's inner loop.
+fn cpu_hash_rate() -> f64 {
+    let factory = hex::decode(FACTORY).unwrap().try_into().unwrap();
+    let init_code_hash = hex::decode(INIT_CODE_HASH).unwrap().try_into().unwrap();
+    let mut salt_head = [0; Preimage::SALT_HEAD_LEN];
+    salt_head[..20].copy_from_slice(&hex::decode(CALLER).unwrap());
+    let hasher = Preimage::new(&factory, &init_code_hash).hasher(&salt_head);
+
+    let start = Instant::now();
+    // count the addresses with a leading zero byte so the work can't be skipped
+    let hits = (0..CPU_SALTS)
+        .into_par_iter()
+        .filter(|salt| hasher.address(&salt.to_le_bytes()[..6])[0] == 0)
+        .count();
+    let elapsed = start.elapsed().as_secs_f64();
+    assert!(hits > 0, "expected some addresses with a leading zero byte");
+
+    CPU_SALTS as f64 / elapsed
+}
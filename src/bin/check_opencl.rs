@@ -1,38 +1,19 @@
 // Check OpenCL setup and devices
-use ocl::{Platform, Device, DeviceType};
+use create2crunch::{devices_json, format_devices, list_devices};
+use ocl::{Device, Platform};
 
 fn main() -> ocl::Result<()> {
-    println!("=== OpenCL Platform and Device Information ===");
-    
-    // Get all platforms
-    let platforms = Platform::list();
-    println!("Found {} OpenCL platform(s):", platforms.len());
-    
-    for (i, platform) in platforms.iter().enumerate() {
-        println!("\nPlatform {}: {}", i, platform.name()?);
-        println!("  Vendor: {}", platform.vendor()?);
-        println!("  Version: {}", platform.version()?);
-        
-        // Get devices for this platform
-        let devices = Device::list(*platform, Some(DeviceType::new().gpu()))?;
-        println!("  GPU Devices: {}", devices.len());
-        
-        for (j, device) in devices.iter().enumerate() {
-            println!("    Device {}: {}", j, device.name()?);
-            println!("      Max Work Group Size: {}", device.max_wg_size()?);
-            println!("      OpenCL Version: {}", device.version()?);
-        }
-        
-        // Also check CPU devices
-        let cpu_devices = Device::list(*platform, Some(DeviceType::new().cpu()))?;
-        if !cpu_devices.is_empty() {
-            println!("  CPU Devices: {}", cpu_devices.len());
-            for (j, device) in cpu_devices.iter().enumerate() {
-                println!("    CPU Device {}: {}", j, device.name()?);
-            }
-        }
+    let platforms = list_devices()?;
+
+    // machine-readable listing for installer scripts and other tooling
+    if std::env::args().skip(1).any(|arg| arg == "--json") {
+        println!("{}", devices_json(&platforms));
+        return Ok(());
     }
-    
+
+    println!("=== OpenCL Platform and Device Information ===");
+    print!("{}", format_devices(&platforms));
+
     // Test default platform and device
     println!("\n=== Default Platform/Device ===");
     let default_platform = Platform::new(ocl::core::default_platform()?);
@@ -48,4 +29,4 @@ fn main() -> ocl::Result<()> {
     }
     
     Ok(())
-}
\ No newline at end of file
+}
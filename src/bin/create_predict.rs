@@ -0,0 +1,35 @@
+// Predict the CREATE addresses of a deployer's upcoming contracts
+use alloy_primitives::hex;
+use create2crunch::create_addresses;
+use std::env;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (deployer, start, count) = match &args[..] {
+        [deployer] => (deployer, "0", "10"),
+        [deployer, start] => (deployer, start.as_str(), "10"),
+        [deployer, start, count] => (deployer, start.as_str(), count.as_str()),
+        _ => {
+            eprintln!("usage: create_predict <deployer> [start_nonce] [count]");
+            process::exit(1);
+        }
+    };
+
+    let Ok(Ok(deployer)) = hex::decode(deployer).map(<[u8; 20]>::try_from) else {
+        eprintln!("could not decode deployer address argument");
+        process::exit(1);
+    };
+    let (Ok(start), Ok(count)) = (start.parse::<u64>(), count.parse::<u64>()) else {
+        eprintln!("invalid start nonce or count");
+        process::exit(1);
+    };
+    let Some(end) = start.checked_add(count) else {
+        eprintln!("nonce range overflows");
+        process::exit(1);
+    };
+
+    for (nonce, address) in create_addresses(&deployer, start..end) {
+        println!("{nonce} => {address}");
+    }
+}
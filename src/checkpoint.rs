@@ -0,0 +1,106 @@
+use alloy_primitives::hex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::output::write_atomically;
+use crate::Config;
+
+/// Where the state of a GPU search is saved, in the working directory.
+pub(crate) const CHECKPOINT_PATH: &str = ".create2crunch_checkpoint";
+
+/// Bumped whenever the meaning of the saved state changes, so that older
+/// checkpoints are ignored rather than misread.
+const CHECKPOINT_VERSION: u32 = 1;
+
+/// The state of a GPU search: the caller, init code hash and random segment in
+/// use, the next nonce to search with them, and the totals of the run so far.
+pub(crate) struct Checkpoint {
+    pub(crate) caller: [u8; 20],
+    pub(crate) init_code_hash: [u8; 32],
+    pub(crate) salt: Vec<u8>,
+    pub(crate) nonce: u64,
+    pub(crate) cumulative_nonce: u64,
+    pub(crate) segments_used: u64,
+}
+
+/// A checkpoint as written to disk.
+#[derive(Serialize, Deserialize)]
+struct SavedCheckpoint {
+    version: u32,
+    caller: String,
+    /// Missing from checkpoints saved before several init code hashes could
+    /// be searched, which are for the first one.
+    #[serde(default)]
+    init_code_hash: Option<String>,
+    salt: String,
+    nonce: u64,
+    cumulative_nonce: u64,
+    segments_used: u64,
+}
+
+impl Checkpoint {
+    /// Read a checkpoint back, making sure it can be resumed with `config`.
+    pub(crate) fn load(path: &Path, config: &Config) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let saved: SavedCheckpoint =
+            serde_json::from_str(&contents).map_err(|_| "the file is corrupt".to_string())?;
+        if saved.version != CHECKPOINT_VERSION {
+            return Err(format!(
+                "it was written by an incompatible version (format {}, expected {})",
+                saved.version, CHECKPOINT_VERSION
+            ));
+        }
+
+        let caller = hex::decode(&saved.caller)
+            .ok()
+            .and_then(|caller| caller.try_into().ok())
+            .ok_or("the file is corrupt")?;
+        if !config.callers.contains(&caller) {
+            return Err("it is for a different caller".to_string());
+        }
+        let init_code_hash = match &saved.init_code_hash {
+            Some(init_code_hash) => hex::decode(init_code_hash)
+                .ok()
+                .and_then(|init_code_hash| init_code_hash.try_into().ok())
+                .ok_or("the file is corrupt")?,
+            None => config.init_code_hashes[0],
+        };
+        if !config.init_code_hashes.contains(&init_code_hash) {
+            return Err("it is for a different init code hash".to_string());
+        }
+        let salt = hex::decode(&saved.salt).map_err(|_| "the file is corrupt")?;
+        if salt.len() != config.salt_random_bytes as usize {
+            return Err("it was made with a different --salt-split".to_string());
+        }
+        let nonce_mask = u64::MAX >> (64 - 8 * config.salt_nonce_bytes as usize);
+        if saved.nonce > nonce_mask {
+            return Err("the file is corrupt".to_string());
+        }
+
+        Ok(Self {
+            caller,
+            init_code_hash,
+            salt,
+            nonce: saved.nonce,
+            cumulative_nonce: saved.cumulative_nonce,
+            segments_used: saved.segments_used,
+        })
+    }
+
+    /// Write the checkpoint to `path`, replacing any previous one.
+    pub(crate) fn save(&self, path: &Path) -> io::Result<()> {
+        let saved = SavedCheckpoint {
+            version: CHECKPOINT_VERSION,
+            caller: format!("0x{}", hex::encode(self.caller)),
+            init_code_hash: Some(format!("0x{}", hex::encode(self.init_code_hash))),
+            salt: format!("0x{}", hex::encode(&self.salt)),
+            nonce: self.nonce,
+            cumulative_nonce: self.cumulative_nonce,
+            segments_used: self.segments_used,
+        };
+        let contents = serde_json::to_string(&saved).expect("checkpoints are always serializable");
+        write_atomically(path, contents.as_bytes())
+    }
+}
@@ -0,0 +1,10 @@
+use arboard::Clipboard;
+
+/// Copy a found solution to the system clipboard. Failures (e.g. no display
+/// server) are reported but never interrupt the search.
+pub(crate) fn copy_to_clipboard(text: &str) {
+    let result = Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text));
+    if let Err(e) = result {
+        eprintln!("Couldn't copy solution to the clipboard: {e}");
+    }
+}
@@ -0,0 +1,81 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+use crate::Create2Error;
+
+/// The positional arguments as given in a `--config` file, e.g.
+///
+/// ```toml
+/// factory_address = "0x0000000000ffe8b47b3e2130213b802212439497"
+/// calling_address = "0x0000000000000000000000000000000000000000"
+/// init_code_hash = "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+/// device = 0
+/// leading_zeroes_threshold = 4
+/// total_zeroes_threshold = 6
+/// ```
+///
+/// Every field is optional, and each is overridden by the corresponding
+/// positional argument or named flag (see [`POSITIONAL_FLAGS`]) when that is
+/// given on the command line as well.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ConfigFile {
+    factory_address: Option<String>,
+    calling_address: Option<String>,
+    init_code_hash: Option<String>,
+    device: Option<Device>,
+    leading_zeroes_threshold: Option<u8>,
+    total_zeroes_threshold: Option<u8>,
+    output_path: Option<String>,
+}
+
+/// The flags that each set one of the positional arguments by name, in the
+/// order of those arguments, so that a single one of them can be overridden
+/// without restating the ones before it.
+pub(crate) const POSITIONAL_FLAGS: [&str; 7] = [
+    "--factory",
+    "--caller",
+    "--init-code-hash",
+    "--device",
+    "--leading",
+    "--total",
+    "--output",
+];
+
+/// A device given by index (`device = 2`) or by name (`device = "5070"`).
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Device {
+    Index(u8),
+    Name(String),
+}
+
+impl ConfigFile {
+    pub(crate) fn load(path: &Path) -> Result<Self, Create2Error> {
+        let shown = path.display();
+        let contents = fs::read_to_string(path).map_err(|e| {
+            Create2Error::ConfigFile(format!("couldn't read config file `{shown}`: {e}"))
+        })?;
+        toml::from_str(&contents)
+            .map_err(|e| Create2Error::ConfigFile(format!("invalid config file `{shown}`: {e}")))
+    }
+
+    /// The values in the order of the positional arguments they stand in for,
+    /// so that they are validated exactly as those are.
+    pub(crate) fn into_positional(self) -> Vec<Option<String>> {
+        let device = self.device.map(|device| match device {
+            Device::Index(index) => index.to_string(),
+            Device::Name(name) => name,
+        });
+        vec![
+            self.factory_address,
+            self.calling_address,
+            self.init_code_hash,
+            device,
+            self.leading_zeroes_threshold.map(|t| t.to_string()),
+            self.total_zeroes_threshold.map(|t| t.to_string()),
+            self.output_path,
+        ]
+    }
+}
@@ -0,0 +1,125 @@
+use alloy_primitives::Address;
+use std::ops::Range;
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::Preimage;
+
+/// Compute the address a factory deploys to with CREATE2: the last 20 bytes of
+/// `keccak256(0xff ++ factory ++ salt ++ init_code_hash)`.
+///
+/// The salts found by the search are laid out as `caller ++ random segment ++
+/// nonce segment`: the first 20 bytes are the calling address (which the
+/// factory checks), and the remaining 12 bytes are what is searched. On the
+/// CPU those are a 6-byte random segment and a 6-byte little-endian nonce; on
+/// the GPU the random bytes, a 4-byte work item index and the nonce bytes as
+/// split by `--salt-split`. With `--no-caller-salt`, the random segment takes
+/// the place of the calling address as well. Either way, the full 32-byte salt
+/// as written to the results file is what goes in here.
+pub fn create2_address(factory: &[u8; 20], salt: &[u8; 32], init_code_hash: &[u8; 32]) -> Address {
+    Preimage::new(factory, init_code_hash).address(salt)
+}
+
+/// Compute the address of the contract a deployer creates with CREATE at the
+/// given account nonce: the last 20 bytes of `keccak256(rlp([deployer, nonce]))`.
+pub fn create_address(deployer: &[u8; 20], nonce: u64) -> Address {
+    // the nonce is RLP-encoded as a big-endian integer without leading zeroes,
+    // using the single-byte form for values below 0x80
+    let nonce_bytes = nonce.to_be_bytes();
+    let nonce_bytes = &nonce_bytes[nonce.leading_zeros() as usize / 8..];
+    let mut encoded_nonce = [0; 9];
+    let encoded_nonce = match nonce_bytes {
+        [] => &[0x80][..],
+        [b] if *b < 0x80 => nonce_bytes,
+        _ => {
+            encoded_nonce[0] = 0x80 + nonce_bytes.len() as u8;
+            encoded_nonce[1..=nonce_bytes.len()].copy_from_slice(nonce_bytes);
+            &encoded_nonce[..=nonce_bytes.len()]
+        }
+    };
+
+    // the whole list is at most 30 bytes, so it always takes the short form
+    let mut hash = Keccak::v256();
+    hash.update(&[0xc0 + 21 + encoded_nonce.len() as u8, 0x80 + 20]);
+    hash.update(deployer);
+    hash.update(encoded_nonce);
+
+    let mut res: [u8; 32] = [0; 32];
+    hash.finalize(&mut res);
+    Address::from_slice(&res[12..])
+}
+
+/// Compute the CREATE addresses for each nonce in a range, e.g. to predict
+/// where a deployer's next few contracts will land.
+pub fn create_addresses(deployer: &[u8; 20], nonces: Range<u64>) -> Vec<(u64, Address)> {
+    nonces
+        .map(|nonce| (nonce, create_address(deployer, nonce)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::hex;
+
+    #[test]
+    fn create2_addresses_match_the_examples_of_eip_1014() {
+        // (factory, salt, init code, address), as given in the EIP
+        let examples: [(&str, &str, &str, &str); 7] = [
+            (
+                "0000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "00",
+                "4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38",
+            ),
+            (
+                "deadbeef00000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "00",
+                "b928f69bb1d91cd65274e3c79d8986362984fda3",
+            ),
+            (
+                "deadbeef00000000000000000000000000000000",
+                "000000000000000000000000feed000000000000000000000000000000000000",
+                "00",
+                "d04116cdd17bebe565eb2422f2497e06cc1c9833",
+            ),
+            (
+                "0000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "deadbeef",
+                "70f2b2914a2a4b783faefb75f459a580616fcb5e",
+            ),
+            (
+                "00000000000000000000000000000000deadbeef",
+                "00000000000000000000000000000000000000000000000000000000cafebabe",
+                "deadbeef",
+                "60f3f640a8508fc6a86d45df051962668e1e8ac7",
+            ),
+            (
+                "00000000000000000000000000000000deadbeef",
+                "00000000000000000000000000000000000000000000000000000000cafebabe",
+                "deadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef\
+                 deadbeef",
+                "1d8bfdc5d46dc4f61d6b6115972536ebe6a8854c",
+            ),
+            (
+                "0000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "",
+                "e33c0c7f7df4809055c3eba6c09cfe4baf1bd9e0",
+            ),
+        ];
+
+        for (factory, salt, init_code, address) in examples {
+            let mut init_code_hash = [0; 32];
+            let mut keccak = Keccak::v256();
+            keccak.update(&hex::decode(init_code).unwrap());
+            keccak.finalize(&mut init_code_hash);
+
+            let factory = hex::decode(factory).unwrap().try_into().unwrap();
+            let salt = hex::decode(salt).unwrap().try_into().unwrap();
+            let derived = create2_address(&factory, &salt, &init_code_hash);
+            assert_eq!(hex::encode(derived), address, "init code 0x{init_code}");
+        }
+    }
+}
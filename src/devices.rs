@@ -0,0 +1,279 @@
+use ocl::enums::{DeviceInfo as OclDeviceInfo, DeviceInfoResult};
+use ocl::{Device, DeviceType, Platform};
+use serde::Serialize;
+use std::fmt::{self, Write as _};
+
+/// An OpenCL platform along with every device it exposes.
+#[derive(Clone, Debug, Serialize)]
+pub struct PlatformInfo {
+    pub index: usize,
+    pub name: String,
+    pub vendor: String,
+    pub version: String,
+    pub devices: Vec<DeviceInfo>,
+}
+
+/// A single OpenCL device. `index` is the value to pass as the device argument
+/// along with its type as `--device-type`, as devices are numbered among those
+/// of the same type on their platform.
+#[derive(Clone, Debug, Serialize)]
+pub struct DeviceInfo {
+    pub index: usize,
+    pub name: String,
+    pub device_type: String,
+    pub version: String,
+    pub max_work_group_size: usize,
+}
+
+/// Enumerate every OpenCL platform and its devices. Both the human-readable
+/// and the JSON listings are rendered from this so that they stay in sync.
+pub fn list_devices() -> ocl::Result<Vec<PlatformInfo>> {
+    let mut platforms = vec![];
+    for (index, platform) in Platform::list().into_iter().enumerate() {
+        let all = Device::list_all(platform)?;
+        let types: Vec<_> = all.iter().map(device_type_name).collect();
+        let devices = all
+            .iter()
+            .zip(&types)
+            .zip(indices_by_type(&types))
+            .map(|((device, device_type), index)| DeviceInfo {
+                index,
+                name: device.name().unwrap_or_else(|_| "Unknown".to_string()),
+                device_type: device_type.to_string(),
+                version: device
+                    .version()
+                    .map(|v| v.to_string())
+                    .unwrap_or_else(|_| "Unknown".to_string()),
+                max_work_group_size: device.max_wg_size().unwrap_or(0),
+            })
+            .collect();
+
+        platforms.push(PlatformInfo {
+            index,
+            name: platform.name().unwrap_or_else(|_| "Unknown".to_string()),
+            vendor: platform.vendor().unwrap_or_else(|_| "Unknown".to_string()),
+            version: platform.version().unwrap_or_else(|_| "Unknown".to_string()),
+            devices,
+        });
+    }
+
+    Ok(platforms)
+}
+
+/// Number the devices of a platform, given their types, the way `--device`
+/// does: each among the devices of its own type, in the order listed.
+fn indices_by_type(types: &[&str]) -> Vec<usize> {
+    let earlier_of_type = |i: usize| types[..i].iter().filter(|t| **t == types[i]).count();
+    (0..types.len()).map(earlier_of_type).collect()
+}
+
+fn device_type_name(device: &Device) -> &'static str {
+    match device.info(OclDeviceInfo::Type) {
+        Ok(DeviceInfoResult::Type(t)) => device_type_label(t),
+        _ => "other",
+    }
+}
+
+fn device_type_label(device_type: DeviceType) -> &'static str {
+    if device_type.contains(DeviceType::GPU) {
+        "gpu"
+    } else if device_type.contains(DeviceType::CPU) {
+        "cpu"
+    } else if device_type.contains(DeviceType::ACCELERATOR) {
+        "accelerator"
+    } else {
+        "other"
+    }
+}
+
+/// Parse a device type as given on the command line.
+pub fn parse_device_type(name: &str) -> Option<DeviceType> {
+    match name {
+        "gpu" => Some(DeviceType::GPU),
+        "cpu" => Some(DeviceType::CPU),
+        "accelerator" => Some(DeviceType::ACCELERATOR),
+        _ => None,
+    }
+}
+
+/// Render the platform/device tree as pretty-printed JSON.
+pub fn devices_json(platforms: &[PlatformInfo]) -> String {
+    serde_json::to_string_pretty(platforms).expect("device listing is always serializable")
+}
+
+/// Render the platform/device tree as human-readable text.
+pub fn format_devices(platforms: &[PlatformInfo]) -> String {
+    let mut out = String::new();
+    writeln!(out, "Found {} OpenCL platform(s):", platforms.len()).unwrap();
+    for platform in platforms {
+        writeln!(out, "\nPlatform {}: {}", platform.index, platform.name).unwrap();
+        writeln!(out, "  Vendor: {}", platform.vendor).unwrap();
+        writeln!(out, "  Version: {}", platform.version).unwrap();
+        writeln!(out, "  Devices: {}", platform.devices.len()).unwrap();
+        for device in &platform.devices {
+            let (i, name, kind) = (device.index, &device.name, &device.device_type);
+            writeln!(out, "    Device {i}: {name} ({kind})").unwrap();
+            writeln!(
+                out,
+                "      Max Work Group Size: {}",
+                device.max_work_group_size
+            )
+            .unwrap();
+            writeln!(out, "      OpenCL Version: {}", device.version).unwrap();
+        }
+    }
+    out
+}
+
+/// Which device on the selected platform to search with: either its index, or
+/// a substring of its name (e.g. `5070` or `Radeon`) so that run scripts keep
+/// working on machines where the index differs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeviceSelector {
+    Index(u8),
+    Name(String),
+}
+
+impl DeviceSelector {
+    /// Index 255 indicates that the CPU will be used instead of OpenCL.
+    pub fn is_cpu(&self) -> bool {
+        *self == Self::Index(255)
+    }
+
+    /// Resolve the selector to one of the devices of the given type on
+    /// `platform`, taking the first device whose name contains the given
+    /// substring (ignoring case) when selecting by name. An index past the last
+    /// device is an error naming the valid ones, rather than wrapping around.
+    pub fn resolve(&self, platform: Platform, device_type: DeviceType) -> ocl::Result<Device> {
+        let devices = Device::list(platform, Some(device_type))?;
+        let device = match self {
+            Self::Index(index) if *index as usize >= devices.len() && !devices.is_empty() => {
+                let kind = device_type_label(device_type);
+                return Err(format!(
+                    "there is no {kind} OpenCL device {index} on the selected platform (valid \
+                     indices: 0 to {}); run with --list-devices to see them",
+                    devices.len() - 1
+                )
+                .into());
+            }
+            Self::Index(index) => devices.get(*index as usize).copied(),
+            Self::Name(name) => {
+                let name = name.to_lowercase();
+                let matches = |device: &Device| {
+                    let device_name = device.name().unwrap_or_default();
+                    device_name.to_lowercase().contains(&name)
+                };
+                devices.into_iter().find(matches)
+            }
+        };
+        device.ok_or_else(|| {
+            let kind = device_type_label(device_type);
+            format!("no {kind} OpenCL device on the selected platform matches {self}").into()
+        })
+    }
+}
+
+impl fmt::Display for DeviceSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Index(index) => write!(f, "{index}"),
+            Self::Name(name) => write!(f, "\"{name}\""),
+        }
+    }
+}
+
+/// Which OpenCL platform to search with, given by `--platform`: either its
+/// index, or a substring of its name (e.g. `AMD` or `Intel`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlatformSelector {
+    Index(usize),
+    Name(String),
+}
+
+impl PlatformSelector {
+    /// Parse a platform as given on the command line: an index if it is all
+    /// digits, otherwise a substring of its name.
+    pub fn parse(value: &str) -> Option<Self> {
+        if value.is_empty() {
+            None
+        } else if value.bytes().all(|b| b.is_ascii_digit()) {
+            value.parse().ok().map(Self::Index)
+        } else {
+            Some(Self::Name(value.to_string()))
+        }
+    }
+
+    /// Resolve the selector to one of `platforms`, taking the first platform
+    /// whose name contains the given substring (ignoring case) when selecting
+    /// by name. The error names the platforms there are to choose from.
+    pub fn resolve(&self, platforms: &[Platform]) -> ocl::Result<Platform> {
+        let platform = match self {
+            Self::Index(index) => platforms.get(*index),
+            Self::Name(name) => {
+                let name = name.to_lowercase();
+                platforms.iter().find(|platform| {
+                    let platform_name = platform.name().unwrap_or_default();
+                    platform_name.to_lowercase().contains(&name)
+                })
+            }
+        };
+        platform.copied().ok_or_else(|| {
+            let available: Vec<_> = platforms
+                .iter()
+                .enumerate()
+                .map(|(i, platform)| {
+                    let name = platform.name().unwrap_or_else(|_| "Unknown".to_string());
+                    format!("{i}: {name}")
+                })
+                .collect();
+            let available = if available.is_empty() {
+                "none".to_string()
+            } else {
+                available.join(", ")
+            };
+            format!("no OpenCL platform matches {self} (available: {available})").into()
+        })
+    }
+}
+
+impl fmt::Display for PlatformSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Index(index) => write!(f, "{index}"),
+            Self::Name(name) => write!(f, "\"{name}\""),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn devices_are_numbered_among_those_of_their_type() {
+        let types = ["cpu", "gpu", "gpu", "accelerator", "cpu", "gpu"];
+        assert_eq!(indices_by_type(&types), [0, 0, 1, 0, 1, 2]);
+
+        // the listing then gives the index that --device selects with the type
+        let device = |(index, device_type): (usize, &&str)| DeviceInfo {
+            index,
+            name: format!("{device_type} {index}"),
+            device_type: device_type.to_string(),
+            version: "OpenCL 3.0".to_string(),
+            max_work_group_size: 256,
+        };
+        let indices = indices_by_type(&types).into_iter();
+        let platform = PlatformInfo {
+            index: 0,
+            name: "Mixed".to_string(),
+            vendor: "Vendor".to_string(),
+            version: "OpenCL 3.0".to_string(),
+            devices: indices.zip(&types).map(device).collect(),
+        };
+        let listing = format_devices(&[platform]);
+        assert!(listing.contains("Device 0: cpu 0 (cpu)"));
+        assert!(listing.contains("Device 0: gpu 0 (gpu)"));
+        assert!(listing.contains("Device 2: gpu 2 (gpu)"));
+        assert!(listing.contains("Device 1: cpu 1 (cpu)"));
+    }
+}
@@ -0,0 +1,86 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Everything that can go wrong constructing a [`Config`](crate::Config) or
+/// running a search.
+#[derive(Debug)]
+pub enum Create2Error {
+    /// A required argument wasn't given; holds the name of the argument.
+    MissingArgument(&'static str),
+    /// An option was given without its value; holds the name of the option.
+    MissingValue(&'static str),
+    /// An argument isn't valid hex; holds the name of the argument.
+    InvalidHex(&'static str),
+    /// A hex argument doesn't have the number of bytes it must have.
+    WrongLength {
+        argument: &'static str,
+        expected: usize,
+    },
+    /// A zero byte threshold is outside the range it may take.
+    ThresholdOutOfRange(&'static str),
+    /// Any other invalid argument, or arguments that can't be combined.
+    InvalidArgument(&'static str),
+    /// The `--config` file couldn't be read or parsed.
+    ConfigFile(String),
+    /// The init code hash couldn't be fetched with `--from-rpc`.
+    Rpc(String),
+    /// The search failed for another reason, e.g. an unreadable reward table.
+    Search(String),
+    /// The results file, or another file written during the search, couldn't
+    /// be created or opened, e.g. for lack of permission or on a read-only
+    /// file system.
+    OutputFile { path: PathBuf, error: io::Error },
+    /// Writing to the terminal failed.
+    Io(io::Error),
+    /// An OpenCL call failed.
+    Opencl(ocl::Error),
+}
+
+impl fmt::Display for Create2Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingArgument(argument) => write!(f, "didn't get the {argument} argument"),
+            Self::MissingValue(option) => write!(f, "didn't get a value for {option}"),
+            Self::InvalidHex(argument) => write!(f, "could not decode {argument} argument"),
+            Self::WrongLength { argument, expected } => {
+                write!(f, "{argument} must be {expected} bytes")
+            }
+            Self::ThresholdOutOfRange(message) | Self::InvalidArgument(message) => {
+                f.write_str(message)
+            }
+            Self::ConfigFile(message) | Self::Rpc(message) | Self::Search(message) => {
+                f.write_str(message)
+            }
+            Self::OutputFile { path, error } => {
+                write!(f, "could not create or open `{}` ({error})", path.display())
+            }
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Opencl(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for Create2Error {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::OutputFile { error, .. } => Some(error),
+            Self::Io(e) => Some(e),
+            Self::Opencl(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Create2Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ocl::Error> for Create2Error {
+    fn from(e: ocl::Error) -> Self {
+        Self::Opencl(e)
+    }
+}
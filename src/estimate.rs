@@ -0,0 +1,188 @@
+use separator::Separatable;
+use std::fmt;
+use std::iter;
+use std::time::Duration;
+
+use crate::Granularity;
+
+/// Private state held by each work item while hashing: the 200-byte keccak
+/// sponge plus the salt and nonce scratch space.
+const WORK_ITEM_PRIVATE_BYTES: u64 = 200 + 16;
+
+/// Birthday estimate of the probability that at least two of `segments` random
+/// salt segments of `segment_bytes` bytes each are identical, i.e. that the run
+/// has started to re-search space it already covered.
+pub fn segment_collision_probability(segment_bytes: u8, segments: u64) -> f64 {
+    if segments < 2 {
+        return 0.0;
+    }
+    let space = 2f64.powi(8 * segment_bytes as i32);
+    let k = segments as f64;
+    -(-k * (k - 1.0) / (2.0 * space)).exp_m1()
+}
+
+/// Estimate the device memory, in bytes, used by a search with the given work
+/// size, number of nonces hashed per work item, and number of solution slots,
+/// on a device with `compute_units` compute units running work groups of up to
+/// `max_work_group_size` items. This covers the message, nonce, and solution
+/// buffers along with the private sponge state of the work items resident on
+/// the device at once, which is at most a full work group per compute unit
+/// however many work items a cycle runs in all.
+pub fn estimated_device_memory(
+    work_size: u32,
+    vector_width: u32,
+    solution_slots: u32,
+    compute_units: u32,
+    max_work_group_size: usize,
+) -> u64 {
+    let vector_width = vector_width.max(1) as u64;
+    let work_items = (work_size as u64 + vector_width - 1) / vector_width;
+    let resident = (compute_units as u64).saturating_mul(max_work_group_size as u64);
+    let buffers = 8 + 8 + solution_slots as u64 * 8;
+    work_items.min(resident) * WORK_ITEM_PRIVATE_BYTES + buffers
+}
+
+/// Probability that a single byte of an address is zero.
+const ZERO_BYTE_PROBABILITY: f64 = 1.0 / 256.0;
+
+/// Probability that a random address has at least `leading` leading zeroes
+/// (counted at `granularity`) or, unless it is above 20, at least `total` zero
+/// bytes in all. Both criteria overlap, as leading zero bytes count towards the
+/// total as well, so the overlap is only counted once.
+pub fn threshold_probability(leading: u8, total: u8, granularity: Granularity) -> f64 {
+    // the leading zeroes fix the first bytes, and with an odd number of
+    // nibbles the high nibble of the byte after them
+    let leading = leading as usize;
+    let (zero_bytes, half_byte) = match granularity {
+        Granularity::Byte => (leading.min(20), 0),
+        Granularity::Nibble => (leading.min(40) / 2, leading.min(40) % 2),
+    };
+    let half_byte_probability: f64 = 1.0 / 16.0;
+    let leading_probability = ZERO_BYTE_PROBABILITY.powi(zero_bytes as i32)
+        * half_byte_probability.powi(half_byte as i32);
+    if total > 20 {
+        return leading_probability;
+    }
+
+    // a byte whose high nibble is known to be zero is zero one time in 16
+    let total = total as usize;
+    let total_probability =
+        at_least_zero_bytes(iter::repeat(ZERO_BYTE_PROBABILITY).take(20), total);
+    let remaining = iter::repeat(half_byte_probability)
+        .take(half_byte)
+        .chain(iter::repeat(ZERO_BYTE_PROBABILITY).take(20 - zero_bytes - half_byte));
+    let total_given_leading = at_least_zero_bytes(remaining, total.saturating_sub(zero_bytes));
+
+    leading_probability + total_probability - leading_probability * total_given_leading
+}
+
+/// How many random addresses a search is expected to try before finding one
+/// with at least `leading` leading zero bytes, one with at least `total` zero
+/// bytes (`None` when `total` is above 20 and so unused), and one meeting
+/// either threshold, which is what a search actually looks for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    pub leading_threshold: u8,
+    pub total_threshold: u8,
+    pub leading: f64,
+    pub total: Option<f64>,
+    pub either: f64,
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let attempts = |expected: f64| expected.round().separated_string();
+        let (threshold, expected) = (self.leading_threshold, attempts(self.leading));
+        writeln!(f, "{threshold} leading zero bytes: 1 in {expected}")?;
+        if let Some(total) = self.total {
+            let (threshold, expected) = (self.total_threshold, attempts(total));
+            writeln!(f, "{threshold} total zero bytes: 1 in {expected}")?;
+            writeln!(f, "either of them: 1 in {}", attempts(self.either))?;
+        }
+        Ok(())
+    }
+}
+
+/// Estimate the difficulty of a search for the given thresholds in bytes, as
+/// the inverse of the chance of a random address meeting each of them: e.g.
+/// `256^5` attempts for 5 leading zero bytes.
+pub fn estimate(leading: u8, total: u8) -> Estimate {
+    let bytes = || iter::repeat(ZERO_BYTE_PROBABILITY).take(20);
+    let total_probability = (total <= 20).then(|| at_least_zero_bytes(bytes(), total as usize));
+    Estimate {
+        leading_threshold: leading,
+        total_threshold: total,
+        leading: 1.0 / threshold_probability(leading, 21, Granularity::Byte),
+        total: total_probability.map(|probability| 1.0 / probability),
+        either: 1.0 / threshold_probability(leading, total, Granularity::Byte),
+    }
+}
+
+/// Probability that at least `needed` of a set of independent bytes are zero,
+/// given the probability of each of them being zero.
+fn at_least_zero_bytes(probabilities: impl Iterator<Item = f64>, needed: usize) -> f64 {
+    // the distribution of the number of zero bytes, one byte at a time
+    let mut counts = vec![1.0];
+    for probability in probabilities {
+        let mut next = vec![0.0; counts.len() + 1];
+        for (zeroes, chance) in counts.iter().enumerate() {
+            next[zeroes] += chance * (1.0 - probability);
+            next[zeroes + 1] += chance * probability;
+        }
+        counts = next;
+    }
+    counts.iter().skip(needed).sum()
+}
+
+/// Suggest a `--work-size` for kernel cycles of about `target`, by scaling the
+/// `work_size` that took `cycle_millis` per cycle, rounded to a multiple of
+/// 2^20 nonces. Nothing can be suggested until a cycle has been timed.
+pub fn suggested_work_size(work_size: u64, cycle_millis: u64, target: Duration) -> Option<u32> {
+    const STEP: f64 = (1 << 20) as f64;
+    if cycle_millis == 0 {
+        return None;
+    }
+    let scaled = work_size as f64 * target.as_millis() as f64 / cycle_millis as f64;
+    let rounded = (scaled / STEP).round().max(1.0) * STEP;
+    Some(rounded.min(u32::MAX as f64) as u32)
+}
+
+/// Describe an expected waiting time given in seconds in the largest unit that
+/// fits, e.g. `~3.2 hours`.
+pub(crate) fn describe_expected_time(secs: f64) -> String {
+    const UNITS: [(&str, f64); 4] = [
+        ("minutes", 60.0),
+        ("hours", 3600.0),
+        ("days", 86_400.0),
+        ("years", 31_557_600.0),
+    ];
+    let (mut unit, mut value) = ("seconds", secs);
+    for (name, length) in UNITS {
+        if secs >= length {
+            (unit, value) = (name, secs / length);
+        }
+    }
+    if value >= 1e6 {
+        format!("~{value:.1e} {unit}")
+    } else {
+        format!("~{value:.1} {unit}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_default_work_size_fits_a_typical_8_gb_device() {
+        // e.g. 46 compute units running work groups of up to 1024 items
+        let needed = estimated_device_memory(0x20000000, 8, 64, 46, 1024);
+        let available: u64 = 8 << 30;
+        assert!(needed < available / 10 * 9);
+        assert_eq!(needed, 46 * 1024 * WORK_ITEM_PRIVATE_BYTES + 8 + 8 + 64 * 8);
+
+        // a cycle smaller than the device can hold only takes what it runs
+        let small = estimated_device_memory(8 * 1000, 8, 64, 46, 1024);
+        assert_eq!(small, 1000 * WORK_ITEM_PRIVATE_BYTES + 8 + 8 + 64 * 8);
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+
+// calldata costs 4 gas per zero byte and 16 per other byte (EIP-2028)
+const CALLDATA_ZERO_BYTE_GAS: u64 = 4;
+const CALLDATA_NONZERO_BYTE_GAS: u64 = 16;
+
+// each byte of deployed code costs 200 gas to store
+const CODE_DEPOSIT_BYTE_GAS: u64 = 200;
+
+/// The gas an address with some zero bytes saves over one without any, as
+/// estimated by [`gas_savings`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GasSavings {
+    /// Saved each time the address is passed in calldata.
+    pub per_use: u64,
+    /// Saved once for each contract deployed with the address as a constant.
+    pub per_deployment: u64,
+}
+
+impl fmt::Display for GasSavings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "saves {} gas per use, {} per deployment",
+            self.per_use, self.per_deployment
+        )
+    }
+}
+
+/// Estimate the gas saved by an address with `leading` leading and `total`
+/// zero bytes in all, compared with one that has none:
+///
+/// - each zero byte is 12 gas cheaper every time the address is passed in
+///   calldata, as calldata costs 4 gas per zero byte rather than 16;
+/// - each leading zero byte shortens the `PUSH` that puts the address on the
+///   stack by a byte in the code of any contract that has it as a constant,
+///   saving the 200 gas of storing that byte of code along with the 16 gas
+///   of sending it in the deploying transaction.
+///
+/// Storing the address costs the same either way, as it takes a whole slot.
+/// How much is saved in all depends on how often the address is used.
+pub fn gas_savings(leading: usize, total: usize) -> GasSavings {
+    let (leading, total) = (leading as u64, total as u64);
+    GasSavings {
+        per_use: total * (CALLDATA_NONZERO_BYTE_GAS - CALLDATA_ZERO_BYTE_GAS),
+        per_deployment: leading * (CODE_DEPOSIT_BYTE_GAS + CALLDATA_NONZERO_BYTE_GAS),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn savings_grow_with_the_zero_bytes() {
+        // 12 gas per zero byte in calldata, 216 per leading zero byte of code
+        let savings = gas_savings(4, 4);
+        assert_eq!(savings.per_use, 48);
+        assert_eq!(savings.per_deployment, 864);
+
+        let savings = gas_savings(0, 5);
+        assert_eq!(savings.per_use, 60);
+        assert_eq!(savings.per_deployment, 0);
+
+        assert_eq!(
+            gas_savings(2, 6).to_string(),
+            "saves 72 gas per use, 432 per deployment"
+        );
+    }
+}
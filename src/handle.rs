@@ -0,0 +1,71 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::shutdown::stop_flag;
+use crate::{cpu_search, gpu_search, Config, Create2Error};
+
+/// A search running on a background thread, started with [`spawn`]. Pausing
+/// takes effect between cycles and keeps the state of the run (the current
+/// random segment, nonce, and statistics), so resuming continues where the
+/// search left off.
+pub struct MiningHandle {
+    paused: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<Result<(), Create2Error>>,
+}
+
+impl MiningHandle {
+    /// Stop searching after the current cycle, e.g. to free the GPU for
+    /// another task.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    /// Continue a paused search.
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    /// Whether the search is currently paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Ask the search to stop after its current batch of work, even while it
+    /// is paused, and write out what it has found so far. Other searches
+    /// aren't affected.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    /// Wait for the search to end, returning its error if it failed.
+    pub fn join(self) -> Result<(), Create2Error> {
+        self.thread.join().unwrap_or_else(|_| {
+            Err(Create2Error::Search(
+                "the search thread panicked".to_string(),
+            ))
+        })
+    }
+}
+
+/// Start a search on a background thread without blocking, on the CPU or
+/// with OpenCL depending on the configured device.
+pub fn spawn(config: Config) -> MiningHandle {
+    let paused = Arc::new(AtomicBool::new(false));
+    let stop = stop_flag();
+    let (search_paused, search_stop) = (Arc::clone(&paused), Arc::clone(&stop));
+    let thread = thread::spawn(move || {
+        if config.is_cpu() {
+            cpu_search(config, &search_paused, &search_stop)
+        } else {
+            gpu_search(config, &search_paused, &search_stop)
+        }
+    });
+
+    MiningHandle {
+        paused,
+        stop,
+        thread,
+    }
+}
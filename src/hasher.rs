@@ -0,0 +1,41 @@
+use alloy_primitives::Address;
+use tiny_keccak::{Hasher, Keccak};
+
+/// Computes the addresses for a series of salts that share the same header
+/// (`0xff ++ factory ++ caller ++ random segment`, as made by
+/// [`Preimage::header`](crate::Preimage::header)), as the CPU search does:
+/// the header is hashed once, and each address only absorbs the remaining
+/// nonce segment and the init code hash. Each address is the same as
+/// [`create2_address`](crate::create2_address) gives for the full salt.
+#[derive(Clone)]
+pub struct SaltHasher {
+    hash_header: Keccak,
+    init_code_hash: [u8; 32],
+}
+
+impl SaltHasher {
+    pub fn new(header: &[u8; 47], init_code_hash: &[u8; 32]) -> Self {
+        let mut hash_header = Keccak::v256();
+        hash_header.update(header);
+        Self {
+            hash_header,
+            init_code_hash: *init_code_hash,
+        }
+    }
+
+    /// The address for the salt ending in the given 6-byte nonce segment.
+    #[inline]
+    pub fn address(&self, nonce_segment: &[u8]) -> Address {
+        // clone the partially-hashed object
+        let mut hash = self.hash_header.clone();
+
+        // update with body and footer (total: 38 bytes)
+        hash.update(nonce_segment);
+        hash.update(&self.init_code_hash);
+
+        // hash the payload and get the result
+        let mut res: [u8; 32] = [0; 32];
+        hash.finalize(&mut res);
+        Address::from_slice(&res[12..])
+    }
+}
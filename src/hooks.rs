@@ -0,0 +1,67 @@
+use std::io;
+use std::process::{Command, Stdio};
+
+/// Run the user's `--on-found` command for a solution without waiting for it.
+/// The command is run by the platform shell with the solution passed in the
+/// `CREATE2_SALT`, `CREATE2_ADDRESS`, `CREATE2_LEADING_ZEROES` and
+/// `CREATE2_TOTAL_ZEROES` environment variables. With `stdout_to_stderr`, what
+/// the command prints is passed on to stderr, to keep it out of results
+/// printed to stdout. Failures to start it are reported but never interrupt
+/// the search.
+pub(crate) fn run_on_found(
+    command: &str,
+    salt: &str,
+    address: &str,
+    leading: usize,
+    total: usize,
+    stdout_to_stderr: bool,
+) {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let spawned = shell
+        .arg(command)
+        .env("CREATE2_SALT", salt)
+        .env("CREATE2_ADDRESS", address)
+        .env("CREATE2_LEADING_ZEROES", leading.to_string())
+        .env("CREATE2_TOTAL_ZEROES", total.to_string())
+        .stdout(if stdout_to_stderr {
+            stderr()
+        } else {
+            Stdio::inherit()
+        })
+        .spawn();
+
+    match spawned {
+        // reap the child in the background so that it doesn't block mining
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(e) => eprintln!("Couldn't run the --on-found command: {e}"),
+    }
+}
+
+/// Our stderr, to hand to the command as its stdout, or else stdout as usual
+/// where it can't be.
+fn stderr() -> Stdio {
+    #[cfg(unix)]
+    {
+        use std::os::unix::io::AsFd;
+        let stderr = io::stderr().as_fd().try_clone_to_owned();
+        stderr.map_or_else(|_| Stdio::inherit(), Stdio::from)
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::io::AsHandle;
+        let stderr = io::stderr().as_handle().try_clone_to_owned();
+        stderr.map_or_else(|_| Stdio::inherit(), Stdio::from)
+    }
+    #[cfg(not(any(unix, windows)))]
+    return Stdio::inherit();
+}
@@ -196,7 +196,19 @@ static inline void keccakf(ulong *a)
   (!(d[16])) + (!(d[17])) + (!(d[18])) + (!(d[19])) \
 >= TOTAL_ZEROES)
 
-#if LEADING_ZEROES == 8
+#if defined(LEADING_NIBBLES)
+static inline bool hasLeading(uchar const *d)
+{
+#pragma unroll
+  for (uint i = 0; i < LEADING_NIBBLES / 2; ++i) {
+    if (d[i] != 0) return false;
+  }
+#if LEADING_NIBBLES % 2
+  if (d[LEADING_NIBBLES / 2] >> 4) return false;
+#endif
+  return true;
+}
+#elif LEADING_ZEROES == 8
 #define hasLeading(d) (!(((uint*)d)[0]) && !(((uint*)d)[1]))
 #elif LEADING_ZEROES == 7
 #define hasLeading(d) (!(((uint*)d)[0]) && !(((uint*)d)[1] & 0x00ffffffu))
@@ -223,9 +235,133 @@ static inline bool hasLeading(uchar const *d)
 }
 #endif
 
+#ifdef MIN_ZERO_RUN
+static inline bool hasZeroRun(uchar const *d)
+{
+  uint run = 0;
+#pragma unroll
+  for (uint i = 0; i < 20; ++i) {
+    run = d[i] ? 0 : run + 1;
+    if (run >= MIN_ZERO_RUN) return true;
+  }
+  return false;
+}
+#endif
+
+#ifdef MAX_ADDRESS
+static inline bool isBelowBound(uchar const *d)
+{
+  uchar const bound[20] = MAX_ADDRESS;
+#pragma unroll
+  for (uint i = 0; i < 20; ++i) {
+    if (d[i] != bound[i]) return d[i] < bound[i];
+  }
+  return false;
+}
+#endif
+
+#ifdef PREFIX
+static inline bool hasPrefix(uchar const *d)
+{
+  uchar const prefix[PREFIX_NIBBLES] = PREFIX;
+#pragma unroll
+  for (uint i = 0; i < PREFIX_NIBBLES; ++i) {
+    uchar nibble = (i & 1) ? (d[i >> 1] & 0x0f) : (d[i >> 1] >> 4);
+    if (nibble != prefix[i]) return false;
+  }
+  return true;
+}
+#endif
+
+#ifdef PATTERN
+static inline bool hasPattern(uchar const *d)
+{
+  uchar const pattern[PATTERN_BYTES] = PATTERN;
+#pragma unroll
+  for (uint i = 0; i < PATTERN_BYTES; ++i) {
+    if (d[PATTERN_OFFSET + i] != pattern[i]) return false;
+  }
+  return true;
+}
+#endif
+
+#ifdef MIN_REPEAT
+// a run of one repeated byte also repeats a byte pair, so only the longest
+// run of bytes equal to the byte two before them needs to be found
+static inline bool hasRepeat(uchar const *d)
+{
+  uint run = 2;
+#pragma unroll
+  for (uint i = 2; i < 20; ++i) {
+    run = (d[i] == d[i - 2]) ? run + 1 : 2;
+    if (run >= MIN_REPEAT) return true;
+  }
+  return false;
+}
+#endif
+
+#ifdef MASK
+static inline bool hasMask(uchar const *d)
+{
+  uchar const positions[MASK_BYTES] = MASK_POSITIONS;
+  uchar const mask[MASK_BYTES] = MASK;
+#pragma unroll
+  for (uint i = 0; i < MASK_BYTES; ++i) {
+    if (d[positions[i]] != mask[i]) return false;
+  }
+  return true;
+}
+#endif
+
+#ifdef TAG
+static inline bool hasTag(uchar const *d)
+{
+  uchar const tag[4] = TAG;
+#pragma unroll
+  for (uint i = 0; i < 4; ++i) {
+    if (d[16 + i] != tag[i]) return false;
+  }
+  return true;
+}
+#else
+#define hasTag(d) true
+#endif
+
+#ifdef SUFFIX
+static inline bool hasSuffix(uchar const *d)
+{
+  uchar const suffix[SUFFIX_BYTES] = SUFFIX;
+#pragma unroll
+  for (uint i = 0; i < SUFFIX_BYTES; ++i) {
+    if (d[20 - SUFFIX_BYTES + i] != suffix[i]) return false;
+  }
+  return true;
+}
+#else
+#define hasSuffix(d) true
+#endif
+
+#if defined(MIN_ZERO_RUN)
+#define isMatch(d) hasZeroRun(d)
+#elif defined(MAX_ADDRESS)
+#define isMatch(d) isBelowBound(d)
+#elif defined(PREFIX)
+#define isMatch(d) hasPrefix(d)
+#elif defined(PATTERN)
+#define isMatch(d) hasPattern(d)
+#elif defined(MIN_REPEAT)
+#define isMatch(d) hasRepeat(d)
+#elif defined(MASK)
+#define isMatch(d) hasMask(d)
+#elif TOTAL_ZEROES <= 20
+#define isMatch(d) (hasLeading(d) || hasTotal(d))
+#else
+#define isMatch(d) hasLeading(d)
+#endif
+
 __kernel void hashMessage(
   __constant uchar const *d_message,
-  __constant uint const *d_nonce,
+  __constant ulong const *d_nonce,
   __global volatile ulong *restrict solutions
 ) {
 
@@ -240,7 +376,7 @@ __kernel void hashMessage(
   for (int vec_i = 0; vec_i < 8; vec_i++) {
 
     // write the control character
-    sponge[0] = 0xffu;
+    sponge[0] = S_0;
 
     sponge[1] = S_1;
     sponge[2] = S_2;
@@ -262,6 +398,9 @@ __kernel void hashMessage(
     sponge[18] = S_18;
     sponge[19] = S_19;
     sponge[20] = S_20;
+
+    // populate the caller segment of the salt, if there is one
+#if CALLER_BYTES
     sponge[21] = S_21;
     sponge[22] = S_22;
     sponge[23] = S_23;
@@ -282,25 +421,29 @@ __kernel void hashMessage(
     sponge[38] = S_38;
     sponge[39] = S_39;
     sponge[40] = S_40;
+#endif
 
-    sponge[41] = d_message[0];
-    sponge[42] = d_message[1];
-    sponge[43] = d_message[2];
-    sponge[44] = d_message[3];
+    // populate the random segment of the salt
+#pragma unroll
+    for (int i = 0; i < RANDOM_BYTES; ++i)
+      sponge[21 + CALLER_BYTES + i] = d_message[i];
 
-    // populate the nonce (vectorized)
+    // populate the work item segment of the salt (vectorized)
     nonce.uint32_t[0] = get_global_id(0) * 8 + vec_i;
-    nonce.uint32_t[1] = d_nonce[0];
-
-  // populate the body of the message with the nonce
-  sponge[45] = nonce.uint8_t[0];
-  sponge[46] = nonce.uint8_t[1];
-  sponge[47] = nonce.uint8_t[2];
-  sponge[48] = nonce.uint8_t[3];
-  sponge[49] = nonce.uint8_t[4];
-  sponge[50] = nonce.uint8_t[5];
-  sponge[51] = nonce.uint8_t[6];
-  sponge[52] = nonce.uint8_t[7];
+    sponge[21 + CALLER_BYTES + RANDOM_BYTES] = nonce.uint8_t[0];
+    sponge[22 + CALLER_BYTES + RANDOM_BYTES] = nonce.uint8_t[1];
+    sponge[23 + CALLER_BYTES + RANDOM_BYTES] = nonce.uint8_t[2];
+    sponge[24 + CALLER_BYTES + RANDOM_BYTES] = nonce.uint8_t[3];
+
+    // populate the nonce segment of the salt with the low bytes of the nonce
+    nonce_t step;
+    step.uint64_t = d_nonce[0];
+#pragma unroll
+    for (int i = 0; i < NONCE_BYTES; ++i)
+      sponge[25 + CALLER_BYTES + RANDOM_BYTES + i] = step.uint8_t[i];
+
+    // the solution records the work item and (the low half of) the nonce
+    nonce.uint32_t[1] = step.uint32_t[0];
 
   sponge[53] = S_53;
   sponge[54] = S_54;
@@ -355,12 +498,7 @@ __kernel void hashMessage(
   keccakf(spongeBuffer);
 
     // determine if the address meets the constraints
-    if (
-      hasLeading(digest) 
-#if TOTAL_ZEROES <= 20
-      || hasTotal(digest)
-#endif
-    ) {
+    if (hasTag(digest) && hasSuffix(digest) && isMatch(digest)) {
       // With vectorization, we need atomic operations to avoid race conditions
       // Find the first available slot atomically
       for (int slot = 0; slot < 64; slot++) { // Check up to 64 solution slots
@@ -2,616 +2,4895 @@
 #![deny(unused_must_use, rust_2018_idioms)]
 
 use alloy_primitives::{hex, Address, FixedBytes};
-use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use checkpoint::{Checkpoint, CHECKPOINT_PATH};
+use config_file::{ConfigFile, POSITIONAL_FLAGS};
 use console::Term;
-use fs4::FileExt;
-use ocl::{Buffer, Context, Device, MemFlags, Platform, ProQue, Program, Queue};
+use ocl::enums::{DeviceInfo as OclDeviceInfo, DeviceInfoResult};
+use ocl::{Buffer, Context, Device, DeviceType, MemFlags, Platform, ProQue, Program, Queue};
 use rand::{thread_rng, Rng};
 use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
 use separator::Separatable;
-use std::error::Error;
 use std::fmt::Write as _;
-use std::fs::{File, OpenOptions};
-use std::io::prelude::*;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::net::{IpAddr, Ipv4Addr, TcpListener};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 use terminal_size::{terminal_size, Height};
-use tiny_keccak::{Hasher, Keccak};
 
+mod checkpoint;
+#[cfg(feature = "clipboard")]
+mod clipboard;
+mod config_file;
+mod create;
+mod devices;
+mod error;
+mod estimate;
+mod gas;
+mod handle;
+mod hasher;
+#[cfg(feature = "hooks")]
+mod hooks;
+mod metrics;
+mod output;
+mod preimage;
+mod rate;
 mod reward;
+#[cfg(feature = "rpc")]
+mod rpc;
+mod score;
+mod selftest;
+mod shutdown;
+mod status;
+pub use create::{create2_address, create_address, create_addresses};
+pub use devices::{
+    devices_json, format_devices, list_devices, parse_device_type, DeviceInfo, DeviceSelector,
+    PlatformInfo, PlatformSelector,
+};
+pub use error::Create2Error;
+use estimate::describe_expected_time;
+pub use estimate::{
+    estimate, estimated_device_memory, segment_collision_probability, suggested_work_size,
+    threshold_probability, Estimate,
+};
+pub use gas::{gas_savings, GasSavings};
+pub use handle::{spawn, MiningHandle};
+pub use hasher::SaltHasher;
+use metrics::Metrics;
+use output::{
+    display_address, utc_timestamp, BufferedResults, Highlights, ResultWriter, SeenSalts, Trace,
+};
+pub use output::{
+    solidity_declaration, tier_file_name, truncated_display, FlushPolicy, FoundRecord, FoundSalt,
+    OutputFormat, ProofBundle,
+};
+pub use preimage::Preimage;
+use rate::RollingRate;
+use reward::reward_at_least;
 pub use reward::Reward;
+pub use score::{
+    contains_any, has_checksum_word, has_mask, has_pattern, has_prefix, has_suffix, is_below,
+    repeat_score, score_address, Granularity, RepeatScore, Score,
+};
+pub use selftest::selftest;
+use shutdown::stop_flag;
+pub use shutdown::{request_stop, stop_on_ctrl_c};
+use status::{State, Status};
 
-// workset size (tweak this!)
+// default workset size (tweak this with `--work-size`!)
 const WORK_SIZE: u32 = 0x20000000; // max. 0x15400000 to abs. max 0xffffffff - increased for RTX 5070 Ti
 
-const WORK_FACTOR: u128 = (WORK_SIZE as u128) / 1_000_000;
 const CONTROL_CHARACTER: u8 = 0xff;
 const MAX_INCREMENTER: u64 = 0xffffffffffff;
 
+// widths of the caller and work item segments of the salt, and the widest the
+// random segment of the GPU salt gets (without a caller segment)
+const CALLER_BYTES: usize = 20;
+const WORK_ITEM_BYTES: usize = 4;
+const MAX_RANDOM_BYTES: usize = 32 - WORK_ITEM_BYTES - 1;
+
+// nonces hashed by each work item, and slots available for GPU solutions
+const VECTOR_WIDTH: u32 = 8;
+const SOLUTION_SLOTS: u32 = 64;
+
+// how often a paused search checks whether it has been resumed
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// how often the state of a GPU search is saved to resume it later
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+
+// the span of time the hash rate shown on the dashboard is measured over
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+// how often a progress line is logged in place of the dashboard with --quiet
+const PROGRESS_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+// the kernel cycle duration the dashboard suggests a --work-size for
+const TARGET_CYCLE_DURATION: Duration = Duration::from_millis(500);
+
+// with several callers or init code hashes, how many nonces the CPU search
+// searches of one of them before moving on to the next, and how long a GPU
+// searches one of them, so that each gets its turn within a minute or so
+// rather than after the weeks it takes to search a whole segment
+const TARGET_CHUNK: u64 = 1 << 30;
+const TARGET_TIME_SLICE: Duration = Duration::from_secs(10);
+
+// how many solutions cpu_iter holds for a caller that is slow to take them
+// before the search waits for it
+const CPU_ITER_BUFFER: usize = 64;
+
 static KERNEL_SRC: &str = include_str!("./kernels/keccak256.cl");
 
+/// Print a line about the search itself: to stdout as usual, or to stderr with
+/// `--no-file`, where stdout only carries the results.
+macro_rules! status {
+    ($config:expr, $($arg:tt)*) => {
+        if $config.no_file {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
 /// Requires three hex-encoded arguments: the address of the contract that will
 /// be calling CREATE2, the address of the caller of said contract *(assuming
 /// the contract calling CREATE2 has frontrunning protection in place - if not
-/// applicable to your use-case you can set it to the null address)*, and the
-/// keccak-256 hash of the bytecode that is provided by the contract calling
-/// CREATE2 that will be used to initialize the new contract. An additional set
-/// of three optional values may be provided: a device to target for OpenCL GPU
-/// search, a threshold for leading zeroes to search for, and a threshold for
-/// total zeroes to search for.
+/// applicable to your use-case you can set it to the null address; several
+/// callers may be given separated by commas, and are searched in turn)*, and
+/// the keccak-256 hash of the bytecode that is provided by the contract calling
+/// CREATE2 that will be used to initialize the new contract *(several hashes
+/// may be given separated by commas, and are searched in turn with each
+/// caller)*. An additional set of three optional values may be provided: a
+/// device to target for OpenCL GPU search (either its index or a substring of
+/// its name, ignoring case; several devices may be given separated by commas,
+/// and search in parallel), a threshold for leading zeroes to search for, and a
+/// threshold for total zeroes to search for. Finally, the path of the file
+/// results are written to may be given (default `efficient_addresses.txt`).
+///
+/// Options prefixed with `--` may be given anywhere after the program name;
+/// they are listed in the README, and each sets one of the fields below. The
+/// fields are public to read; set them through [`ConfigBuilder`], as changing
+/// them on a built config skips its checks.
 pub struct Config {
+    /// The factory (or other contract) that calls CREATE2.
     pub factory_address: [u8; 20],
-    pub calling_address: [u8; 20],
-    pub init_code_hash: [u8; 32],
-    pub gpu_device: u8,
+    /// The callers of the factory, each searched in turn.
+    pub callers: Vec<[u8; 20]>,
+    /// The init code hashes to search for, each with every caller in turn.
+    pub init_code_hashes: Vec<[u8; 32]>,
+    /// The file results are appended to (`efficient_addresses.txt` by default).
+    pub output_path: PathBuf,
+    /// The OpenCL devices to search on, or none to search on the CPU.
+    pub gpu_devices: Vec<DeviceSelector>,
+    /// The leading zero bytes (or nibbles) the GPU search reports addresses from.
     pub leading_zeroes_threshold: u8,
+    /// The total zero bytes the GPU search reports addresses from.
     pub total_zeroes_threshold: u8,
+    /// With `--preimage`, also record the CREATE2 preimage of each solution.
+    pub show_preimage: bool,
+    /// With `--min-zero-run`, the run of zero bytes to match instead of the thresholds.
+    pub min_zero_run: Option<u8>,
+    /// The random segment of the GPU salt, from `--salt-split`.
+    pub salt_random_bytes: u8,
+    /// The nonce segment of the GPU salt, from `--salt-split`.
+    pub salt_nonce_bytes: u8,
+    /// With `--tag`, the last four bytes an address must end with.
+    pub tag: Option<[u8; 4]>,
+    /// With `--clipboard`, copy the first solution to the clipboard.
+    pub copy_to_clipboard: bool,
+    /// With `--beat`, the address that a solution has to be rarer than.
+    pub beat_address: Option<[u8; 20]>,
+    /// How solutions are written, from `--format`.
+    pub output_format: OutputFormat,
+    /// How often the dashboard or status line is redrawn.
+    pub refresh_interval: Duration,
+    /// With `--tiered`, write each solution to the file for its leading zero bytes.
+    pub tiered_output: bool,
+    /// With `--quiet-until`, the leading zero bytes to only show a heartbeat until.
+    pub quiet_until: Option<u8>,
+    /// With `--max-address`, the bound addresses must be below instead of the thresholds.
+    pub max_address: Option<[u8; 20]>,
+    /// With `--on-found`, the command run for each solution.
+    pub on_found_command: Option<String>,
+    /// With `--highlights`, the file the milestones of the run are appended to.
+    pub highlights_path: Option<PathBuf>,
+    /// With `--trace`, the file the coverage of the search is appended to.
+    pub trace_path: Option<PathBuf>,
+    /// With `--status-file`, the file kept up to date with the state of the search.
+    pub status_path: Option<PathBuf>,
+    /// With `--word`, the word the checksummed address must contain.
+    pub checksum_word: Option<String>,
+    /// With `--sort-on-exit`, sort the results by reward once the search ends.
+    pub sort_on_exit: bool,
+    /// The type of OpenCL device searched with, from `--device-type`.
+    pub device_type: DeviceType,
+    /// With `--preview`, also show each solution truncated as wallets show it.
+    pub show_preview: bool,
+    /// With `--prefix`, the hex digits (as nibbles) addresses must start with.
+    pub prefix: Option<Vec<u8>>,
+    /// With `--resume`, continue from the checkpoint of a previous GPU search.
+    pub resume: bool,
+    /// With `--reward-table`, the file of rewards to score addresses with.
+    pub reward_table: Option<PathBuf>,
+    /// The nonces hashed per kernel cycle, from `--work-size`.
+    pub work_size: u32,
+    /// With `--max-results`, the number of solutions to stop at.
+    pub max_results: Option<u64>,
+    /// With `--duration`, how long to search for.
+    pub max_duration: Option<Duration>,
+    /// Whether addresses are written checksummed, unless `--no-checksum` is given.
+    pub checksum_addresses: bool,
+    /// With `--pattern`, the offset and bytes addresses must contain instead of the thresholds.
+    pub pattern: Option<(usize, Vec<u8>)>,
+    /// With `--bench`, the kernel cycles to measure instead of searching.
+    pub bench_batches: Option<u32>,
+    /// With `--salt-seed`, the random segment to search from.
+    pub salt_seed: Option<Vec<u8>>,
+    /// Whether leading zeroes count whole bytes or nibbles, from `--granularity`.
+    pub zero_granularity: Granularity,
+    /// With `--cpu-threads`, the threads the CPU search runs on.
+    pub cpu_threads: Option<usize>,
+    /// With `--min-reward`, the reward an address needs to be recorded.
+    pub min_reward: Option<u32>,
+    /// With `--metrics-port`, the port the metrics are served on.
+    pub metrics_port: Option<u16>,
+    /// The address the metrics are served on, from `--metrics-addr`.
+    pub metrics_addr: IpAddr,
+    /// With `--quiet`, log lines instead of redrawing the dashboard.
+    pub quiet: bool,
+    /// With `--suffix`, the bytes addresses must end with.
+    pub suffix: Option<Vec<u8>>,
+    /// With `--min-repeat`, the run of a repeating byte or pair to match instead of the thresholds.
+    pub min_repeat: Option<u8>,
+    /// Whether the salt starts with the caller, unless `--no-caller-salt` is given.
+    pub use_caller_salt: bool,
+    /// With `--check`, only check that the search could start.
+    pub check: bool,
+    /// With `--once`, stop after the first address with a zero byte.
+    pub once: bool,
+    /// With `--annotate`, start the output of the run with a header.
+    pub annotate: bool,
+    /// The share of the last kernel cycle the host sleeps for, from `--poll-backoff`.
+    pub poll_backoff: f64,
+    /// With `--sequential-salt`, count the random segment up rather than pick a fresh one.
+    pub sequential_salt: bool,
+    /// With `--verbose`, print the defines of each kernel.
+    pub verbose: bool,
+    /// With `--mask`, the byte required at each position instead of the thresholds.
+    pub mask: Option<Vec<(usize, u8)>>,
+    /// With `--auto-device`, fall back to the first device when the index is out of range.
+    pub auto_device: bool,
+    /// With `--no-file`, print results to stdout rather than write them to a file.
+    pub no_file: bool,
+    /// With `--best`, keep only the best solution found in the time given.
+    pub best: bool,
+    /// With `--deny`, the runs of bytes addresses must not contain.
+    pub deny: Vec<Vec<u8>>,
+    /// With `--flush-every`, when results held back are written out.
+    pub flush_every: Option<FlushPolicy>,
+    /// With `--platform`, the OpenCL platform to search on.
+    pub platform: Option<PlatformSelector>,
+    /// With `--max-file-size`, the size the results file is rotated at.
+    pub max_file_size: Option<u64>,
+    /// The byte the hashed message starts with (`0xff` for CREATE2).
+    pub control_character: u8,
+    /// With `--show-gas`, add the estimated gas savings to each result.
+    pub show_gas: bool,
+}
+
+/// Builds a [`Config`] without going through command line arguments, with the
+/// same defaults and checks. Each setter stands in for the argument of the same
+/// name, and [`build`](Self::build) checks what it was given just as it checks
+/// that argument. Options without a setter, which only matter to the binary,
+/// keep their defaults.
+#[derive(Clone, Debug)]
+pub struct ConfigBuilder {
+    factory_address: Option<[u8; 20]>,
+    callers: Vec<[u8; 20]>,
+    init_code_hashes: Vec<[u8; 32]>,
+    gpu_devices: Vec<DeviceSelector>,
+    leading_zeroes_threshold: u8,
+    total_zeroes_threshold: u8,
+    output_path: PathBuf,
+    show_preimage: bool,
+    min_zero_run_string: Option<String>,
+    salt_split_string: Option<String>,
+    tag_string: Option<String>,
+    copy_to_clipboard: bool,
+    beat_address_string: Option<String>,
+    output_format_string: Option<String>,
+    refresh_interval_string: Option<String>,
+    tiered_output: bool,
+    quiet_until_string: Option<String>,
+    max_address_string: Option<String>,
+    on_found_command: Option<String>,
+    highlights_path: Option<PathBuf>,
+    trace_path: Option<PathBuf>,
+    status_path: Option<PathBuf>,
+    checksum_word: Option<String>,
+    sort_on_exit: bool,
+    device_type_string: Option<String>,
+    show_preview: bool,
+    prefix_string: Option<String>,
+    resume: bool,
+    reward_table: Option<PathBuf>,
+    work_size_string: Option<String>,
+    max_results_string: Option<String>,
+    max_duration_string: Option<String>,
+    checksum_addresses: bool,
+    pattern_string: Option<String>,
+    bench_batches_string: Option<String>,
+    salt_seed_string: Option<String>,
+    zero_granularity_string: Option<String>,
+    cpu_threads_string: Option<String>,
+    min_reward_string: Option<String>,
+    metrics_port_string: Option<String>,
+    metrics_addr_string: Option<String>,
+    quiet: bool,
+    suffix_string: Option<String>,
+    min_repeat_string: Option<String>,
+    use_caller_salt: bool,
+    check: bool,
+    once: bool,
+    annotate: bool,
+    poll_backoff_string: Option<String>,
+    sequential_salt: bool,
+    verbose: bool,
+    mask_string: Option<String>,
+    auto_device: bool,
+    no_file: bool,
+    best: bool,
+    deny_string: Option<String>,
+    flush_every_string: Option<String>,
+    platform_string: Option<String>,
+    max_file_size_string: Option<String>,
+    control_character: u8,
+    show_gas: bool,
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        Self {
+            factory_address: None,
+            callers: vec![],
+            init_code_hashes: vec![],
+            gpu_devices: vec![DeviceSelector::Index(255)], // indicates that CPU will be used.
+            leading_zeroes_threshold: 3,
+            total_zeroes_threshold: 5,
+            output_path: PathBuf::from("efficient_addresses.txt"),
+            show_preimage: false,
+            min_zero_run_string: None,
+            salt_split_string: None,
+            tag_string: None,
+            copy_to_clipboard: false,
+            beat_address_string: None,
+            output_format_string: None,
+            refresh_interval_string: None,
+            tiered_output: false,
+            quiet_until_string: None,
+            max_address_string: None,
+            on_found_command: None,
+            highlights_path: None,
+            trace_path: None,
+            status_path: None,
+            checksum_word: None,
+            sort_on_exit: false,
+            device_type_string: None,
+            show_preview: false,
+            prefix_string: None,
+            resume: false,
+            reward_table: None,
+            work_size_string: None,
+            max_results_string: None,
+            max_duration_string: None,
+            checksum_addresses: true,
+            pattern_string: None,
+            bench_batches_string: None,
+            salt_seed_string: None,
+            zero_granularity_string: None,
+            cpu_threads_string: None,
+            min_reward_string: None,
+            metrics_port_string: None,
+            metrics_addr_string: None,
+            quiet: false,
+            suffix_string: None,
+            min_repeat_string: None,
+            use_caller_salt: true,
+            check: false,
+            once: false,
+            annotate: false,
+            poll_backoff_string: None,
+            sequential_salt: false,
+            verbose: false,
+            mask_string: None,
+            auto_device: false,
+            no_file: false,
+            best: false,
+            deny_string: None,
+            flush_every_string: None,
+            platform_string: None,
+            max_file_size_string: None,
+            control_character: CONTROL_CHARACTER,
+            show_gas: false,
+        }
+    }
 }
 
 /// Validate the provided arguments and construct the Config struct.
 impl Config {
-    pub fn new(mut args: std::env::Args) -> Result<Self, &'static str> {
+    pub fn new(args: impl Iterator<Item = String>) -> Result<Self, Create2Error> {
+        ConfigBuilder::from_args(args)?.build()
+    }
+
+    /// Start building a config programmatically rather than from arguments.
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+}
+
+impl ConfigBuilder {
+    /// Parse the command line arguments into a builder, leaving the checks that
+    /// apply to programmatic use as well to [`ConfigBuilder::build`].
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, Create2Error> {
         // get args, skipping first arg (program name)
         args.next();
 
-        let Some(factory_address_string) = args.next() else {
-            return Err("didn't get a factory_address argument");
+        // separate `--` options from the positional arguments
+        let mut builder = Self::default();
+        let mut positional = vec![];
+        let mut named = vec![None; POSITIONAL_FLAGS.len()];
+        let mut config_file = None;
+        let mut rpc_url = None;
+        let mut factory_selector = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--config"));
+                    };
+                    config_file = Some(PathBuf::from(value));
+                }
+                "--preimage" => builder.show_preimage = true,
+                "--min-zero-run" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--min-zero-run"));
+                    };
+                    builder.min_zero_run_string = Some(value);
+                }
+                "--salt-split" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--salt-split"));
+                    };
+                    builder.salt_split_string = Some(value);
+                }
+                "--tag" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--tag"));
+                    };
+                    builder.tag_string = Some(value);
+                }
+                "--clipboard" if cfg!(feature = "clipboard") => builder.copy_to_clipboard = true,
+                "--clipboard" => {
+                    return Err(Create2Error::InvalidArgument(
+                        "--clipboard requires the `clipboard` feature",
+                    ))
+                }
+                "--beat" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--beat"));
+                    };
+                    builder.beat_address_string = Some(value);
+                }
+                "--format" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--format"));
+                    };
+                    builder.output_format_string = Some(value);
+                }
+                "--bundle" => builder.output_format_string = Some("bundle".to_string()),
+                "--refresh-interval" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--refresh-interval"));
+                    };
+                    builder.refresh_interval_string = Some(value);
+                }
+                "--tiered" => builder.tiered_output = true,
+                "--quiet-until" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--quiet-until"));
+                    };
+                    builder.quiet_until_string = Some(value);
+                }
+                "--max-address" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--max-address"));
+                    };
+                    builder.max_address_string = Some(value);
+                }
+                "--on-found" if cfg!(feature = "hooks") => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--on-found"));
+                    };
+                    builder.on_found_command = Some(value);
+                }
+                "--on-found" => {
+                    return Err(Create2Error::InvalidArgument(
+                        "--on-found requires the `hooks` feature",
+                    ))
+                }
+                "--from-rpc" if cfg!(feature = "rpc") => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--from-rpc"));
+                    };
+                    rpc_url = Some(value);
+                }
+                "--factory-selector" if cfg!(feature = "rpc") => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--factory-selector"));
+                    };
+                    factory_selector = Some(value);
+                }
+                "--from-rpc" | "--factory-selector" => {
+                    return Err(Create2Error::InvalidArgument(
+                        "--from-rpc requires the `rpc` feature",
+                    ))
+                }
+                "--highlights" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--highlights"));
+                    };
+                    builder.highlights_path = Some(PathBuf::from(value));
+                }
+                "--trace" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--trace"));
+                    };
+                    builder.trace_path = Some(PathBuf::from(value));
+                }
+                "--status-file" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--status-file"));
+                    };
+                    builder.status_path = Some(PathBuf::from(value));
+                }
+                "--word" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--word"));
+                    };
+                    builder.checksum_word = Some(value);
+                }
+                "--sort-on-exit" => builder.sort_on_exit = true,
+                "--preview" => builder.show_preview = true,
+                "--quiet" => builder.quiet = true,
+                "--resume" => builder.resume = true,
+                "--no-caller-salt" => builder.use_caller_salt = false,
+                "--check" => builder.check = true,
+                "--once" => builder.once = true,
+                "--annotate" => builder.annotate = true,
+                "--sequential-salt" => builder.sequential_salt = true,
+                "--verbose" => builder.verbose = true,
+                "--auto-device" => builder.auto_device = true,
+                "--no-file" => builder.no_file = true,
+                "--best" => builder.best = true,
+                "--show-gas" => builder.show_gas = true,
+                "--poll-backoff" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--poll-backoff"));
+                    };
+                    builder.poll_backoff_string = Some(value);
+                }
+                "--prefix" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--prefix"));
+                    };
+                    builder.prefix_string = Some(value);
+                }
+                "--reward-table" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--reward-table"));
+                    };
+                    builder.reward_table = Some(PathBuf::from(value));
+                }
+                "--work-size" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--work-size"));
+                    };
+                    builder.work_size_string = Some(value);
+                }
+                "--max-results" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--max-results"));
+                    };
+                    builder.max_results_string = Some(value);
+                }
+                "--duration" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--duration"));
+                    };
+                    builder.max_duration_string = Some(value);
+                }
+                "--no-checksum" => builder.checksum_addresses = false,
+                "--pattern" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--pattern"));
+                    };
+                    builder.pattern_string = Some(value);
+                }
+                "--bench" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--bench"));
+                    };
+                    builder.bench_batches_string = Some(value);
+                }
+                "--salt-seed" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--salt-seed"));
+                    };
+                    builder.salt_seed_string = Some(value);
+                }
+                "--granularity" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--granularity"));
+                    };
+                    builder.zero_granularity_string = Some(value);
+                }
+                "--cpu-threads" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--cpu-threads"));
+                    };
+                    builder.cpu_threads_string = Some(value);
+                }
+                "--min-reward" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--min-reward"));
+                    };
+                    builder.min_reward_string = Some(value);
+                }
+                "--metrics-port" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--metrics-port"));
+                    };
+                    builder.metrics_port_string = Some(value);
+                }
+                "--metrics-addr" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--metrics-addr"));
+                    };
+                    builder.metrics_addr_string = Some(value);
+                }
+                "--suffix" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--suffix"));
+                    };
+                    builder.suffix_string = Some(value);
+                }
+                "--min-repeat" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--min-repeat"));
+                    };
+                    builder.min_repeat_string = Some(value);
+                }
+                "--mask" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--mask"));
+                    };
+                    builder.mask_string = Some(value);
+                }
+                "--deny" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--deny"));
+                    };
+                    builder.deny_string = Some(value);
+                }
+                "--flush-every" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--flush-every"));
+                    };
+                    builder.flush_every_string = Some(value);
+                }
+                "--platform" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--platform"));
+                    };
+                    builder.platform_string = Some(value);
+                }
+                "--max-file-size" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--max-file-size"));
+                    };
+                    builder.max_file_size_string = Some(value);
+                }
+                "--device-type" => {
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue("--device-type"));
+                    };
+                    builder.device_type_string = Some(value);
+                }
+                flag if POSITIONAL_FLAGS.contains(&flag) => {
+                    let slot = POSITIONAL_FLAGS.iter().position(|f| *f == flag).unwrap();
+                    let Some(value) = args.next() else {
+                        return Err(Create2Error::MissingValue(POSITIONAL_FLAGS[slot]));
+                    };
+                    named[slot] = Some(value);
+                }
+                _ if arg.starts_with("--") => {
+                    return Err(Create2Error::InvalidArgument(
+                        "unrecognized option supplied",
+                    ))
+                }
+                _ => positional.push(arg),
+            }
+        }
+
+        if rpc_url.is_some() != factory_selector.is_some() {
+            return Err(Create2Error::InvalidArgument(
+                "--from-rpc and --factory-selector must be given together",
+            ));
+        }
+
+        // with --from-rpc, the init code hash is fetched rather than given, so
+        // the positional arguments after the caller move up one place
+        let mut positional: Vec<_> = positional.into_iter().map(Some).collect();
+        if rpc_url.is_some() && positional.len() > 2 {
+            positional.insert(2, None);
+        }
+
+        // values from a config file stand in for the positional arguments that
+        // aren't given on the command line, and the named flags override both
+        let mut slots = match config_file {
+            Some(path) => ConfigFile::load(&path)?.into_positional(),
+            None => vec![],
         };
-        let Some(calling_address_string) = args.next() else {
-            return Err("didn't get a calling_address argument");
+        for (i, arg) in positional.into_iter().enumerate() {
+            match slots.get_mut(i) {
+                Some(slot) if arg.is_some() => *slot = arg,
+                Some(_) => {}
+                None => slots.push(arg),
+            }
+        }
+        for (i, value) in named.into_iter().enumerate() {
+            if value.is_some() {
+                if slots.len() <= i {
+                    slots.resize(i + 1, None);
+                }
+                slots[i] = value;
+            }
+        }
+        let mut args = slots.into_iter();
+
+        // convert main arguments from hex string to vector of bytes, accepting
+        // them as copied from a block explorer
+        if let Some(factory_address_string) = args.next().flatten() {
+            let Ok(factory_address_vec) = hex::decode(normalize_hex(&factory_address_string))
+            else {
+                return Err(Create2Error::InvalidHex("factory address"));
+            };
+            let Ok(factory_address) = factory_address_vec.try_into() else {
+                return Err(Create2Error::WrongLength {
+                    argument: "factory address",
+                    expected: 20,
+                });
+            };
+            builder.factory_address = Some(factory_address);
+        }
+
+        // several callers may be given, separated by commas
+        if let Some(calling_address_string) = args.next().flatten() {
+            for calling_address_string in calling_address_string.split(',') {
+                let Ok(calling_address_vec) = hex::decode(normalize_hex(calling_address_string))
+                else {
+                    return Err(Create2Error::InvalidHex("calling address"));
+                };
+                let Ok(calling_address) = calling_address_vec.try_into() else {
+                    return Err(Create2Error::WrongLength {
+                        argument: "calling address",
+                        expected: 20,
+                    });
+                };
+                builder.callers.push(calling_address);
+            }
+        }
+
+        // several init code hashes may be given, separated by commas
+        if let Some(init_code_hash_string) = args.next().flatten() {
+            for init_code_hash_string in init_code_hash_string.split(',') {
+                let Ok(init_code_hash_vec) = hex::decode(normalize_hex(init_code_hash_string))
+                else {
+                    return Err(Create2Error::InvalidHex("initialization code hash"));
+                };
+                let Ok(init_code_hash) = init_code_hash_vec.try_into() else {
+                    return Err(Create2Error::WrongLength {
+                        argument: "initialization code hash",
+                        expected: 32,
+                    });
+                };
+                builder.init_code_hashes.push(init_code_hash);
+            }
+        }
+
+        // convert gpu arguments to u8 values
+        // a device may be given by index, or by a substring of its name, and
+        // several devices may be given separated by commas
+        if let Some(gpu_device_string) = args.next().flatten() {
+            builder.gpu_devices.clear();
+            for gpu_device_string in gpu_device_string.split(',') {
+                let gpu_device = if gpu_device_string.bytes().all(|b| b.is_ascii_digit()) {
+                    let Ok(index) = gpu_device_string.parse::<u8>() else {
+                        return Err(Create2Error::InvalidArgument("invalid gpu device value"));
+                    };
+                    DeviceSelector::Index(index)
+                } else {
+                    DeviceSelector::Name(gpu_device_string.to_string())
+                };
+                builder.gpu_devices.push(gpu_device);
+            }
+        }
+
+        if let Some(leading_zeroes_threshold_string) = args.next().flatten() {
+            let Ok(leading_zeroes_threshold) = leading_zeroes_threshold_string.parse::<u8>() else {
+                return Err(Create2Error::InvalidArgument(
+                    "invalid leading zeroes threshold value supplied",
+                ));
+            };
+            builder.leading_zeroes_threshold = leading_zeroes_threshold;
+        }
+        if let Some(total_zeroes_threshold_string) = args.next().flatten() {
+            let Ok(total_zeroes_threshold) = total_zeroes_threshold_string.parse::<u8>() else {
+                return Err(Create2Error::InvalidArgument(
+                    "invalid total zeroes threshold value supplied",
+                ));
+            };
+            builder.total_zeroes_threshold = total_zeroes_threshold;
+        }
+        if let Some(output_path) = args.next().flatten() {
+            builder.output_path = PathBuf::from(output_path);
+        }
+
+        // ask the factory for the init code hash, once its address is known
+        #[cfg(feature = "rpc")]
+        if let (Some(url), Some(selector)) = (rpc_url, factory_selector) {
+            if !builder.init_code_hashes.is_empty() {
+                return Err(Create2Error::InvalidArgument(
+                    "--from-rpc can't be combined with an init code hash",
+                ));
+            }
+            let Some(factory_address) = builder.factory_address else {
+                return Err(Create2Error::MissingArgument("factory_address"));
+            };
+            let Some(selector) = rpc::parse_selector(&selector) else {
+                return Err(Create2Error::InvalidArgument(
+                    "invalid value for --factory-selector. (expected 4 bytes of hex or a \
+                     function signature)",
+                ));
+            };
+            let init_code_hash = rpc::fetch_init_code_hash(&url, &factory_address, &selector)
+                .map_err(Create2Error::Rpc)?;
+            builder.init_code_hashes.push(init_code_hash);
+        }
+
+        Ok(builder)
+    }
+
+    /// Set the address of the factory contract that deploys with CREATE2.
+    pub fn factory_address(mut self, factory_address: [u8; 20]) -> Self {
+        self.factory_address = Some(factory_address);
+        self
+    }
+
+    /// Set the address the salts are for, i.e. the one calling the factory.
+    pub fn calling_address(mut self, calling_address: [u8; 20]) -> Self {
+        self.callers = vec![calling_address];
+        self
+    }
+
+    /// Set the keccak-256 hash of the init code of the contract to deploy.
+    pub fn init_code_hash(mut self, init_code_hash: [u8; 32]) -> Self {
+        self.init_code_hashes = vec![init_code_hash];
+        self
+    }
+
+    /// Set the OpenCL device to search with by index, or 255 to search on the
+    /// CPU (the default).
+    pub fn gpu_device(mut self, gpu_device: u8) -> Self {
+        self.gpu_devices = vec![DeviceSelector::Index(gpu_device)];
+        self
+    }
+
+    /// Set the leading zero bytes an address needs to be kept (default 3).
+    pub fn leading_zeroes_threshold(mut self, threshold: u8) -> Self {
+        self.leading_zeroes_threshold = threshold;
+        self
+    }
+
+    /// Set the total zero bytes an address needs to be kept, or 255 to only
+    /// consider leading zero bytes (default 5).
+    pub fn total_zeroes_threshold(mut self, threshold: u8) -> Self {
+        self.total_zeroes_threshold = threshold;
+        self
+    }
+
+    /// Set the byte that starts the message hashed for each address, for
+    /// salted-deployment schemes other than CREATE2 (default `0xff`).
+    pub fn control_character(mut self, control_character: u8) -> Self {
+        self.control_character = control_character;
+        self
+    }
+
+    /// Set the file results are appended to, as with `--output`.
+    pub fn output_path(mut self, output_path: impl Into<PathBuf>) -> Self {
+        self.output_path = output_path.into();
+        self
+    }
+
+    /// Set how solutions are written, as with `--format`.
+    pub fn output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format_string = Some(output_format.name().to_string());
+        self
+    }
+
+    /// Print results to stdout rather than write them to a file, as with
+    /// `--no-file`.
+    pub fn no_file(mut self, no_file: bool) -> Self {
+        self.no_file = no_file;
+        self
+    }
+
+    /// Stop once `max_results` solutions have been found, as with
+    /// `--max-results`.
+    pub fn max_results(mut self, max_results: u64) -> Self {
+        self.max_results_string = Some(max_results.to_string());
+        self
+    }
+
+    /// Stop searching after `duration`, as with `--duration`.
+    pub fn max_duration(mut self, duration: Duration) -> Self {
+        self.max_duration_string = Some(format!("{}s", duration.as_secs_f64()));
+        self
+    }
+
+    /// Match a run of at least `min_zero_run` zero bytes anywhere instead of
+    /// the thresholds, as with `--min-zero-run`.
+    pub fn min_zero_run(mut self, min_zero_run: u8) -> Self {
+        self.min_zero_run_string = Some(min_zero_run.to_string());
+        self
+    }
+
+    /// Only match addresses ending in `tag`, as with `--tag`.
+    pub fn tag(mut self, tag: [u8; 4]) -> Self {
+        self.tag_string = Some(format!("0x{}", hex::encode(tag)));
+        self
+    }
+
+    /// Only match addresses ending in `suffix`, as with `--suffix`.
+    pub fn suffix(mut self, suffix: &[u8]) -> Self {
+        self.suffix_string = Some(format!("0x{}", hex::encode(suffix)));
+        self
+    }
+
+    /// Match addresses starting with the hex digits `prefix` instead of the
+    /// thresholds, as with `--prefix`.
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix_string = Some(prefix.to_string());
+        self
+    }
+
+    /// Match addresses with `bytes` starting at byte `offset` instead of the
+    /// thresholds, as with `--pattern`.
+    pub fn pattern(mut self, offset: usize, bytes: &[u8]) -> Self {
+        self.pattern_string = Some(format!("{offset}:0x{}", hex::encode(bytes)));
+        self
+    }
+
+    /// Match addresses with the given byte at each given position instead of
+    /// the thresholds, as with `--mask`.
+    pub fn mask(mut self, mask: &[(usize, u8)]) -> Self {
+        let entries: Vec<String> = mask
+            .iter()
+            .map(|(position, byte)| format!("{position}:0x{byte:02x}"))
+            .collect();
+        self.mask_string = Some(entries.join(","));
+        self
+    }
+
+    /// Match addresses with a run of at least `min_repeat` bytes repeating a
+    /// byte or pair instead of the thresholds, as with `--min-repeat`.
+    pub fn min_repeat(mut self, min_repeat: u8) -> Self {
+        self.min_repeat_string = Some(min_repeat.to_string());
+        self
+    }
+
+    /// Keep searching until an address rarer than `beat_address` is found, as
+    /// with `--beat`.
+    pub fn beat_address(mut self, beat_address: [u8; 20]) -> Self {
+        self.beat_address_string = Some(format!("0x{}", hex::encode(beat_address)));
+        self
+    }
+
+    /// Match addresses below `max_address` instead of the thresholds, as with
+    /// `--max-address`.
+    pub fn max_address(mut self, max_address: [u8; 20]) -> Self {
+        self.max_address_string = Some(format!("0x{}", hex::encode(max_address)));
+        self
+    }
+
+    /// Only record addresses with a reward of at least `min_reward`, as with
+    /// `--min-reward`.
+    pub fn min_reward(mut self, min_reward: u32) -> Self {
+        self.min_reward_string = Some(min_reward.to_string());
+        self
+    }
+
+    /// Skip any address containing one of the runs of bytes in `deny`, as
+    /// with `--deny`.
+    pub fn deny(mut self, deny: &[&[u8]]) -> Self {
+        let runs: Vec<String> = deny
+            .iter()
+            .map(|run| format!("0x{}", hex::encode(run)))
+            .collect();
+        self.deny_string = Some(runs.join(","));
+        self
+    }
+
+    /// Split the GPU salt into `random` random bytes and `nonce` nonce bytes,
+    /// as with `--salt-split`.
+    pub fn salt_split(mut self, random: u8, nonce: u8) -> Self {
+        self.salt_split_string = Some(format!("{random}:{nonce}"));
+        self
+    }
+
+    /// Search from the random segment `salt_seed`, as with `--salt-seed`.
+    pub fn salt_seed(mut self, salt_seed: &[u8]) -> Self {
+        self.salt_seed_string = Some(format!("0x{}", hex::encode(salt_seed)));
+        self
+    }
+
+    /// Search the part of the salt that would hold the caller as well, as with
+    /// `--no-caller-salt` when `use_caller_salt` is false.
+    pub fn use_caller_salt(mut self, use_caller_salt: bool) -> Self {
+        self.use_caller_salt = use_caller_salt;
+        self
+    }
+
+    /// Search on the CPU with `cpu_threads` threads, as with `--cpu-threads`.
+    pub fn cpu_threads(mut self, cpu_threads: usize) -> Self {
+        self.cpu_threads_string = Some(cpu_threads.to_string());
+        self
+    }
+
+    /// Hash `work_size` nonces per kernel cycle on the GPU, as with
+    /// `--work-size`.
+    pub fn work_size(mut self, work_size: u32) -> Self {
+        self.work_size_string = Some(work_size.to_string());
+        self
+    }
+
+    /// Check the settings and construct the Config struct.
+    pub fn build(self) -> Result<Config, Create2Error> {
+        let Self {
+            factory_address,
+            callers,
+            init_code_hashes,
+            gpu_devices,
+            mut leading_zeroes_threshold,
+            mut total_zeroes_threshold,
+            output_path,
+            show_preimage,
+            min_zero_run_string,
+            salt_split_string,
+            tag_string,
+            copy_to_clipboard,
+            beat_address_string,
+            output_format_string,
+            refresh_interval_string,
+            tiered_output,
+            quiet_until_string,
+            max_address_string,
+            on_found_command,
+            highlights_path,
+            trace_path,
+            status_path,
+            checksum_word,
+            sort_on_exit,
+            device_type_string,
+            show_preview,
+            prefix_string,
+            resume,
+            reward_table,
+            work_size_string,
+            max_results_string,
+            max_duration_string,
+            checksum_addresses,
+            pattern_string,
+            bench_batches_string,
+            salt_seed_string,
+            zero_granularity_string,
+            cpu_threads_string,
+            min_reward_string,
+            metrics_port_string,
+            metrics_addr_string,
+            quiet,
+            suffix_string,
+            min_repeat_string,
+            use_caller_salt,
+            check,
+            once,
+            annotate,
+            poll_backoff_string,
+            sequential_salt,
+            verbose,
+            mask_string,
+            auto_device,
+            no_file,
+            best,
+            deny_string,
+            flush_every_string,
+            platform_string,
+            max_file_size_string,
+            control_character,
+            show_gas,
+        } = self;
+
+        let Some(factory_address) = factory_address else {
+            return Err(Create2Error::MissingArgument("factory_address"));
         };
-        let Some(init_code_hash_string) = args.next() else {
-            return Err("didn't get an init_code_hash argument");
+        if !use_caller_salt && callers.len() > 1 {
+            return Err(Create2Error::InvalidArgument(
+                "--no-caller-salt leaves the caller out of the salt, so only one may be given",
+            ));
+        }
+        if callers.is_empty() {
+            return Err(Create2Error::MissingArgument("calling_address"));
+        }
+        if init_code_hashes.is_empty() {
+            return Err(Create2Error::MissingArgument("init_code_hash"));
+        }
+
+        if gpu_devices.len() > 1 && gpu_devices.iter().any(DeviceSelector::is_cpu) {
+            return Err(Create2Error::InvalidArgument(
+                "the CPU (device 255) can't be combined with OpenCL devices",
+            ));
+        }
+        let is_cpu = gpu_devices[0].is_cpu();
+
+        let zero_granularity = match zero_granularity_string {
+            Some(value) => match Granularity::parse(&value) {
+                Some(zero_granularity) => zero_granularity,
+                None => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --granularity. (valid: byte | nibble)",
+                    ))
+                }
+            },
+            None => Granularity::Byte,
         };
 
-        let gpu_device_string = match args.next() {
-            Some(arg) => arg,
-            None => String::from("255"), // indicates that CPU will be used.
+        match zero_granularity {
+            Granularity::Byte if leading_zeroes_threshold > 20 => {
+                return Err(Create2Error::ThresholdOutOfRange(
+                    "invalid value for leading zeroes threshold argument. (valid: 0..=20)",
+                ));
+            }
+            Granularity::Nibble if leading_zeroes_threshold > 40 => {
+                return Err(Create2Error::ThresholdOutOfRange(
+                    "invalid value for leading zeroes threshold argument. (valid: 0..=40 nibbles)",
+                ));
+            }
+            _ => {}
+        }
+        if total_zeroes_threshold > 20 && total_zeroes_threshold != 255 {
+            return Err(Create2Error::ThresholdOutOfRange(
+                "invalid value for total zeroes threshold argument. (valid: 0..=20 | 255)",
+            ));
+        }
+
+        let min_zero_run = match min_zero_run_string {
+            Some(value) => match value.parse::<u8>() {
+                Ok(run @ 1..=20) => Some(run),
+                _ => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --min-zero-run. (valid: 1..=20)",
+                    ))
+                }
+            },
+            None => None,
         };
-        let leading_zeroes_threshold_string = match args.next() {
-            Some(arg) => arg,
-            None => String::from("3"),
+
+        let (salt_random_bytes, salt_nonce_bytes) = match salt_split_string {
+            Some(value) => {
+                let Some((random, nonce)) = value.split_once(':') else {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --salt-split. (expected R:N)",
+                    ));
+                };
+                let (Ok(random), Ok(nonce)) = (random.parse::<u8>(), nonce.parse::<u8>()) else {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --salt-split. (expected R:N)",
+                    ));
+                };
+                (random, nonce)
+            }
+            None if use_caller_salt => (4, 4),
+            None => (24, 4),
         };
-        let total_zeroes_threshold_string = match args.next() {
-            Some(arg) => arg,
-            None => String::from("5"),
+        if salt_random_bytes == 0 || salt_nonce_bytes == 0 {
+            return Err(Create2Error::InvalidArgument(
+                "both segments of --salt-split must be at least one byte",
+            ));
+        }
+        // the caller, random, work item and nonce segments make up the salt
+        let caller_bytes = if use_caller_salt { CALLER_BYTES } else { 0 };
+        let salt_bytes =
+            caller_bytes + salt_random_bytes as usize + WORK_ITEM_BYTES + salt_nonce_bytes as usize;
+        if salt_bytes != 32 {
+            return Err(Create2Error::InvalidArgument(
+                "the segments of --salt-split must sum to 8 bytes, or 28 with --no-caller-salt",
+            ));
+        }
+        if salt_nonce_bytes > 8 {
+            return Err(Create2Error::InvalidArgument(
+                "the nonce segment of --salt-split can't exceed 8 bytes",
+            ));
+        }
+
+        let tag = match tag_string {
+            Some(value) => {
+                let Ok(tag_vec) = hex::decode(normalize_hex(&value)) else {
+                    return Err(Create2Error::InvalidHex("--tag"));
+                };
+                let Ok(tag) = tag_vec.try_into() else {
+                    return Err(Create2Error::WrongLength {
+                        argument: "--tag",
+                        expected: 4,
+                    });
+                };
+                Some(tag)
+            }
+            None => None,
         };
 
-        // convert main arguments from hex string to vector of bytes
-        let Ok(factory_address_vec) = hex::decode(factory_address_string) else {
-            return Err("could not decode factory address argument");
+        let suffix = match suffix_string {
+            Some(value) => {
+                let Ok(suffix) = hex::decode(normalize_hex(&value)) else {
+                    return Err(Create2Error::InvalidHex("--suffix"));
+                };
+                if suffix.is_empty() || suffix.len() > 20 {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --suffix. (expected 1 to 20 bytes)",
+                    ));
+                }
+                Some(suffix)
+            }
+            None => None,
         };
-        let Ok(calling_address_vec) = hex::decode(calling_address_string) else {
-            return Err("could not decode calling address argument");
+
+        // the denied runs of bytes are given as `0xBYTES,0xBYTES`
+        let mut deny = vec![];
+        for value in deny_string.iter().flat_map(|value| value.split(',')) {
+            let Ok(bytes) = hex::decode(normalize_hex(value)) else {
+                return Err(Create2Error::InvalidHex("--deny"));
+            };
+            if bytes.is_empty() || bytes.len() > 20 {
+                return Err(Create2Error::InvalidArgument(
+                    "invalid value for --deny. (expected runs of 1 to 20 bytes)",
+                ));
+            }
+            deny.push(bytes);
+        }
+
+        let flush_every = match flush_every_string {
+            Some(value) => match FlushPolicy::parse(&value) {
+                Some(policy) => Some(policy),
+                None => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --flush-every. (e.g. 100, 10s or 5m)",
+                    ))
+                }
+            },
+            None => None,
         };
-        let Ok(init_code_hash_vec) = hex::decode(init_code_hash_string) else {
-            return Err("could not decode initialization code hash argument");
+
+        let beat_address = match beat_address_string {
+            Some(value) => {
+                let Ok(beat_address_vec) = hex::decode(normalize_hex(&value)) else {
+                    return Err(Create2Error::InvalidHex("--beat address"));
+                };
+                let Ok(beat_address) = beat_address_vec.try_into() else {
+                    return Err(Create2Error::WrongLength {
+                        argument: "--beat address",
+                        expected: 20,
+                    });
+                };
+                Some(beat_address)
+            }
+            None => None,
         };
 
-        // convert from vector to fixed array
-        let Ok(factory_address) = factory_address_vec.try_into() else {
-            return Err("invalid length for factory address argument");
+        // anything rarer than the reference address has at least one more
+        // leading zero byte or at least one more zero byte in total, so use
+        // that as the effective threshold and check exactly once found
+        if let Some(beat_address) = beat_address {
+            if min_zero_run.is_some() {
+                return Err(Create2Error::InvalidArgument(
+                    "--beat can't be combined with --min-zero-run",
+                ));
+            }
+            if zero_granularity == Granularity::Nibble {
+                return Err(Create2Error::InvalidArgument(
+                    "--beat compares whole zero bytes and can't be combined with --granularity",
+                ));
+            }
+            let Score { leading, total, .. } = score_address(&beat_address);
+            if leading >= 20 {
+                return Err(Create2Error::InvalidArgument(
+                    "the --beat address has no zero bytes left to beat",
+                ));
+            }
+            leading_zeroes_threshold = leading as u8 + 1;
+            total_zeroes_threshold = if total < 20 { total as u8 + 1 } else { 255 };
+        }
+
+        let refresh_interval = match refresh_interval_string {
+            Some(value) => match value.parse::<f64>() {
+                Ok(secs) if (0.05..=60.0).contains(&secs) => Duration::from_secs_f64(secs),
+                _ => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --refresh-interval. (valid: 0.05..=60)",
+                    ))
+                }
+            },
+            None => Duration::from_secs(1),
         };
-        let Ok(calling_address) = calling_address_vec.try_into() else {
-            return Err("invalid length for calling address argument");
+
+        if let Some(word) = &checksum_word {
+            if word.is_empty() || word.len() > 40 || !word.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(Create2Error::InvalidArgument(
+                    "invalid value for --word. (expected 1 to 40 hex digits)",
+                ));
+            }
+            if !is_cpu {
+                return Err(Create2Error::InvalidArgument(
+                    "--word is only supported when searching on the CPU",
+                ));
+            }
+        }
+        if status_path.is_some() && is_cpu {
+            return Err(Create2Error::InvalidArgument(
+                "--status-file is only supported when searching with OpenCL",
+            ));
+        }
+        if resume && is_cpu {
+            return Err(Create2Error::InvalidArgument(
+                "--resume is only supported when searching with OpenCL",
+            ));
+        }
+        if sequential_salt && is_cpu {
+            return Err(Create2Error::InvalidArgument(
+                "--sequential-salt is only supported when searching with OpenCL",
+            ));
+        }
+        if verbose && is_cpu {
+            return Err(Create2Error::InvalidArgument(
+                "--verbose is only supported when searching with OpenCL",
+            ));
+        }
+        if auto_device && is_cpu {
+            return Err(Create2Error::InvalidArgument(
+                "--auto-device is only supported when searching with OpenCL",
+            ));
+        }
+        if platform_string.is_some() && is_cpu {
+            return Err(Create2Error::InvalidArgument(
+                "--platform is only supported when searching with OpenCL",
+            ));
+        }
+        if no_file && (tiered_output || sort_on_exit) {
+            return Err(Create2Error::InvalidArgument(
+                "--no-file can't be combined with --tiered or --sort-on-exit",
+            ));
+        }
+        if max_file_size_string.is_some() && (tiered_output || no_file) {
+            return Err(Create2Error::InvalidArgument(
+                "--max-file-size can't be combined with --tiered or --no-file",
+            ));
+        }
+        if resume && gpu_devices.len() > 1 {
+            return Err(Create2Error::InvalidArgument(
+                "--resume is only supported when searching with a single device",
+            ));
+        }
+
+        let output_format = match output_format_string {
+            Some(value) => match OutputFormat::parse(&value) {
+                Some(output_format) => output_format,
+                None => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --format. (valid: text | json | bundle | solidity)",
+                    ))
+                }
+            },
+            None => OutputFormat::Text,
         };
-        let Ok(init_code_hash) = init_code_hash_vec.try_into() else {
-            return Err("invalid length for initialization code hash argument");
+
+        let device_type = match device_type_string {
+            Some(value) => match parse_device_type(&value) {
+                Some(device_type) => device_type,
+                None => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --device-type. (valid: gpu | cpu | accelerator)",
+                    ))
+                }
+            },
+            None => DeviceType::GPU,
         };
 
-        // convert gpu arguments to u8 values
-        let Ok(gpu_device) = gpu_device_string.parse::<u8>() else {
-            return Err("invalid gpu device value");
+        let max_file_size = match max_file_size_string {
+            Some(value) => match parse_size(&value) {
+                Some(size) => Some(size),
+                None => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --max-file-size. (e.g. 500K, 100M or 1G)",
+                    ))
+                }
+            },
+            None => None,
         };
-        let Ok(leading_zeroes_threshold) = leading_zeroes_threshold_string.parse::<u8>() else {
-            return Err("invalid leading zeroes threshold value supplied");
+
+        let platform = match platform_string {
+            Some(value) => match PlatformSelector::parse(&value) {
+                Some(platform) => Some(platform),
+                None => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --platform. (expected an index or part of a name)",
+                    ))
+                }
+            },
+            None => None,
         };
-        let Ok(total_zeroes_threshold) = total_zeroes_threshold_string.parse::<u8>() else {
-            return Err("invalid total zeroes threshold value supplied");
+
+        let quiet_until = match quiet_until_string {
+            Some(value) => match value.parse::<u8>() {
+                Ok(leading @ 1..=20) => Some(leading),
+                _ => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --quiet-until. (valid: 1..=20)",
+                    ))
+                }
+            },
+            None => None,
         };
 
-        if leading_zeroes_threshold > 20 {
-            return Err("invalid value for leading zeroes threshold argument. (valid: 0..=20)");
-        }
-        if total_zeroes_threshold > 20 && total_zeroes_threshold != 255 {
-            return Err("invalid value for total zeroes threshold argument. (valid: 0..=20 | 255)");
+        let max_address = match max_address_string {
+            Some(value) => {
+                let Ok(max_address_vec) = hex::decode(normalize_hex(&value)) else {
+                    return Err(Create2Error::InvalidHex("--max-address"));
+                };
+                let Ok(max_address) = max_address_vec.try_into() else {
+                    return Err(Create2Error::WrongLength {
+                        argument: "--max-address",
+                        expected: 20,
+                    });
+                };
+                Some(max_address)
+            }
+            None => None,
+        };
+        if max_address.is_some() && (min_zero_run.is_some() || beat_address.is_some()) {
+            return Err(Create2Error::InvalidArgument(
+                "--max-address can't be combined with --min-zero-run or --beat",
+            ));
         }
 
-        Ok(Self {
-            factory_address,
-            calling_address,
-            init_code_hash,
-            gpu_device,
-            leading_zeroes_threshold,
-            total_zeroes_threshold,
-        })
-    }
-}
+        let max_results = match max_results_string {
+            Some(value) => match value.parse::<u64>() {
+                Ok(max) if max > 0 => Some(max),
+                _ => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --max-results. (expected at least 1)",
+                    ))
+                }
+            },
+            None => None,
+        };
 
-/// Given a Config object with a factory address, a caller address, and a
-/// keccak-256 hash of the contract initialization code, search for salts that
-/// will enable the factory contract to deploy a contract to a gas-efficient
-/// address via CREATE2.
-///
-/// The 32-byte salt is constructed as follows:
+        let bench_batches = match bench_batches_string {
+            Some(value) => match value.parse::<u32>() {
+                Ok(batches) if batches > 0 => Some(batches),
+                _ => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --bench. (expected at least 1)",
+                    ))
+                }
+            },
+            None => None,
+        };
+        if bench_batches.is_some() && is_cpu {
+            return Err(Create2Error::InvalidArgument(
+                "--bench is only supported when searching with OpenCL",
+            ));
+        }
+        if bench_batches.is_some() && gpu_devices.len() > 1 {
+            return Err(Create2Error::InvalidArgument(
+                "--bench measures a single device at a time",
+            ));
+        }
+        if bench_batches.is_some() && check {
+            return Err(Create2Error::InvalidArgument(
+                "--check can't be combined with --bench",
+            ));
+        }
+
+        // the seed replaces the random segment of the salt, which is six bytes
+        // on the CPU and as many as --salt-split gives on the GPU, plus the
+        // caller segment on the CPU without one
+        let salt_seed = match salt_seed_string {
+            Some(value) => {
+                let Ok(salt_seed) = hex::decode(normalize_hex(&value)) else {
+                    return Err(Create2Error::InvalidHex("--salt-seed"));
+                };
+                let expected = if is_cpu {
+                    6 + CALLER_BYTES - caller_bytes
+                } else {
+                    salt_random_bytes as usize
+                };
+                if salt_seed.len() != expected {
+                    return Err(Create2Error::WrongLength {
+                        argument: "--salt-seed",
+                        expected,
+                    });
+                }
+                Some(salt_seed)
+            }
+            None => None,
+        };
+        if salt_seed.is_some() && gpu_devices.len() > 1 {
+            return Err(Create2Error::InvalidArgument(
+                "--salt-seed is only supported when searching with a single device",
+            ));
+        }
+
+        let cpu_threads = match cpu_threads_string {
+            Some(value) => match value.parse::<usize>() {
+                Ok(threads) if threads > 0 => Some(threads),
+                _ => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --cpu-threads. (expected at least 1)",
+                    ))
+                }
+            },
+            None => None,
+        };
+        if cpu_threads.is_some() && !is_cpu {
+            return Err(Create2Error::InvalidArgument(
+                "--cpu-threads is only supported when searching on the CPU",
+            ));
+        }
+
+        let min_reward = match min_reward_string {
+            Some(value) => match value.parse::<u32>() {
+                Ok(min_reward) => Some(min_reward),
+                Err(_) => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --min-reward. (expected a whole number)",
+                    ))
+                }
+            },
+            None => None,
+        };
+
+        let metrics_port = match metrics_port_string {
+            Some(value) => match value.parse::<u16>() {
+                Ok(port) if port > 0 => Some(port),
+                _ => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --metrics-port. (valid: 1..=65535)",
+                    ))
+                }
+            },
+            None => None,
+        };
+        if metrics_port.is_some() && is_cpu {
+            return Err(Create2Error::InvalidArgument(
+                "--metrics-port is only supported when searching with OpenCL",
+            ));
+        }
+
+        // the metrics are only served locally unless another address is given
+        if metrics_addr_string.is_some() && metrics_port.is_none() {
+            return Err(Create2Error::InvalidArgument(
+                "--metrics-addr requires --metrics-port",
+            ));
+        }
+        let metrics_addr = match metrics_addr_string {
+            Some(value) => match value.trim().parse::<IpAddr>() {
+                Ok(addr) => addr,
+                Err(_) => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --metrics-addr. (expected an IP address)",
+                    ))
+                }
+            },
+            None => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        };
+
+        let max_duration = match max_duration_string {
+            Some(value) => match parse_duration(&value) {
+                Some(duration) => Some(duration),
+                None => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --duration. (e.g. 90s, 30m, 2h or 1d)",
+                    ))
+                }
+            },
+            None => None,
+        };
+
+        // the work size may be given in decimal or hex, up to what a kernel can
+        // be enqueued with
+        let work_size = match work_size_string {
+            Some(value) => {
+                let digits = normalize_hex(&value);
+                let parsed = if digits.len() < value.trim().len() {
+                    u32::from_str_radix(digits, 16)
+                } else {
+                    digits.parse::<u32>()
+                };
+                match parsed {
+                    Ok(work_size) if work_size >= VECTOR_WIDTH => work_size,
+                    _ => {
+                        return Err(Create2Error::InvalidArgument(
+                            "invalid value for --work-size. (valid: 8..=0xffffffff)",
+                        ))
+                    }
+                }
+            }
+            None => WORK_SIZE,
+        };
+
+        // the prefix is kept as individual nibbles so odd lengths can be matched
+        let prefix = match prefix_string {
+            Some(value) => {
+                let digits = value.strip_prefix("0x").unwrap_or(&value);
+                let nibbles: Option<Vec<u8>> = digits
+                    .chars()
+                    .map(|c| c.to_digit(16).map(|n| n as u8))
+                    .collect();
+                match nibbles {
+                    Some(nibbles) if (1..=40).contains(&nibbles.len()) => Some(nibbles),
+                    _ => {
+                        return Err(Create2Error::InvalidArgument(
+                            "invalid value for --prefix. (expected 1 to 40 hex digits)",
+                        ))
+                    }
+                }
+            }
+            None => None,
+        };
+        if prefix.is_some()
+            && (min_zero_run.is_some() || beat_address.is_some() || max_address.is_some())
+        {
+            return Err(Create2Error::InvalidArgument(
+                "--prefix can't be combined with --min-zero-run, --beat or --max-address",
+            ));
+        }
+
+        // the pattern is given as `OFFSET:0xBYTES`, and must fit in the address
+        let pattern = match pattern_string {
+            Some(value) => {
+                let Some((offset, bytes)) = value.split_once(':') else {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --pattern. (expected OFFSET:0xBYTES)",
+                    ));
+                };
+                let offset = offset.trim().parse::<usize>();
+                let bytes = hex::decode(normalize_hex(bytes));
+                let (Ok(offset), Ok(bytes)) = (offset, bytes) else {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --pattern. (expected OFFSET:0xBYTES)",
+                    ));
+                };
+                let end = offset.checked_add(bytes.len());
+                if bytes.is_empty() || end.map_or(true, |end| end > 20) {
+                    return Err(Create2Error::InvalidArgument(
+                        "the --pattern must fit in the 20 bytes of the address",
+                    ));
+                }
+                Some((offset, bytes))
+            }
+            None => None,
+        };
+        if pattern.is_some()
+            && (min_zero_run.is_some()
+                || beat_address.is_some()
+                || max_address.is_some()
+                || prefix.is_some())
+        {
+            return Err(Create2Error::InvalidArgument(
+                "--pattern can't be combined with --min-zero-run, --beat, --max-address, --prefix",
+            ));
+        }
+
+        // every address has a run of two bytes repeating a byte pair
+        let min_repeat = match min_repeat_string {
+            Some(value) => match value.parse::<u8>() {
+                Ok(run @ 3..=20) => Some(run),
+                _ => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --min-repeat. (valid: 3..=20)",
+                    ))
+                }
+            },
+            None => None,
+        };
+        if min_repeat.is_some()
+            && (min_zero_run.is_some()
+                || beat_address.is_some()
+                || max_address.is_some()
+                || prefix.is_some()
+                || pattern.is_some())
+        {
+            return Err(Create2Error::InvalidArgument(
+                "--min-repeat can't be combined with --min-zero-run, --beat, --max-address, \
+                 --prefix, --pattern",
+            ));
+        }
+
+        // the mask is given as `POS:VAL,POS:VAL`, with each value a byte in hex
+        // or decimal, and may name a position twice only with the same value
+        let mask = match mask_string {
+            Some(value) => {
+                let mut mask: Vec<(usize, u8)> = vec![];
+                for entry in value.split(',') {
+                    let Some((position, byte)) = entry.trim().split_once(':') else {
+                        return Err(Create2Error::InvalidArgument(
+                            "invalid value for --mask. (expected POS:VAL,POS:VAL,...)",
+                        ));
+                    };
+                    let byte = match byte.strip_prefix("0x").or_else(|| byte.strip_prefix("0X")) {
+                        Some(digits) => u8::from_str_radix(digits, 16),
+                        None => byte.parse::<u8>(),
+                    };
+                    let (Ok(position), Ok(byte)) = (position.parse::<usize>(), byte) else {
+                        return Err(Create2Error::InvalidArgument(
+                            "invalid value for --mask. (expected POS:VAL,POS:VAL,...)",
+                        ));
+                    };
+                    if position >= 20 {
+                        return Err(Create2Error::InvalidArgument(
+                            "the --mask positions must be below 20, the bytes of the address",
+                        ));
+                    }
+                    match mask.iter().find(|(seen, _)| *seen == position) {
+                        Some(&(_, seen)) if seen != byte => {
+                            return Err(Create2Error::InvalidArgument(
+                                "the --mask gives conflicting values for the same position",
+                            ))
+                        }
+                        Some(_) => {}
+                        None => mask.push((position, byte)),
+                    }
+                }
+                mask.sort_unstable();
+                Some(mask)
+            }
+            None => None,
+        };
+        if mask.is_some()
+            && (min_zero_run.is_some()
+                || beat_address.is_some()
+                || max_address.is_some()
+                || prefix.is_some()
+                || pattern.is_some()
+                || min_repeat.is_some())
+        {
+            return Err(Create2Error::InvalidArgument(
+                "--mask can't be combined with --min-zero-run, --beat, --max-address, --prefix, \
+                 --pattern, --min-repeat",
+            ));
+        }
+
+        // a self-check stops at the first address with a zero byte, which is
+        // found within seconds
+        if once
+            && (min_zero_run.is_some()
+                || beat_address.is_some()
+                || max_address.is_some()
+                || prefix.is_some()
+                || pattern.is_some()
+                || min_repeat.is_some()
+                || mask.is_some()
+                || min_reward.is_some())
+        {
+            return Err(Create2Error::InvalidArgument(
+                "--once can't be combined with --min-zero-run, --beat, --max-address, --prefix, \
+                 --pattern, --min-repeat, --mask, --min-reward",
+            ));
+        }
+        let max_results = if once { Some(1) } else { max_results };
+
+        // the best address is only known once the time is up
+        if best && max_duration.is_none() {
+            return Err(Create2Error::InvalidArgument("--best requires --duration"));
+        }
+        if best && (max_results.is_some() || beat_address.is_some()) {
+            return Err(Create2Error::InvalidArgument(
+                "--best can't be combined with --max-results, --beat or --once",
+            ));
+        }
+
+        let poll_backoff = match poll_backoff_string {
+            Some(value) => match value.parse::<f64>() {
+                Ok(fraction) if (0.0..=1.0).contains(&fraction) => fraction,
+                _ => {
+                    return Err(Create2Error::InvalidArgument(
+                        "invalid value for --poll-backoff. (valid: 0..=1)",
+                    ))
+                }
+            },
+            None => 0.98,
+        };
+        if once {
+            leading_zeroes_threshold = 1;
+            total_zeroes_threshold = 1;
+        }
+
+        Ok(Config {
+            factory_address,
+            callers,
+            init_code_hashes,
+            output_path,
+            gpu_devices,
+            leading_zeroes_threshold,
+            total_zeroes_threshold,
+            show_preimage,
+            min_zero_run,
+            salt_random_bytes,
+            salt_nonce_bytes,
+            tag,
+            copy_to_clipboard,
+            beat_address,
+            output_format,
+            refresh_interval,
+            tiered_output,
+            quiet_until,
+            max_address,
+            on_found_command,
+            highlights_path,
+            trace_path,
+            status_path,
+            checksum_word,
+            sort_on_exit,
+            device_type,
+            show_preview,
+            prefix,
+            resume,
+            reward_table,
+            work_size,
+            max_results,
+            max_duration,
+            checksum_addresses,
+            pattern,
+            bench_batches,
+            salt_seed,
+            zero_granularity,
+            cpu_threads,
+            min_reward,
+            metrics_port,
+            metrics_addr,
+            quiet,
+            suffix,
+            min_repeat,
+            use_caller_salt,
+            check,
+            once,
+            annotate,
+            poll_backoff,
+            sequential_salt,
+            verbose,
+            mask,
+            auto_device,
+            no_file,
+            best,
+            deny,
+            flush_every,
+            platform,
+            max_file_size,
+            control_character,
+            show_gas,
+        })
+    }
+}
+
+/// Trim surrounding whitespace and a leading `0x` or `0X` from a hex argument.
+fn normalize_hex(value: &str) -> &str {
+    let value = value.trim();
+    value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value)
+}
+
+/// Parse a span of time such as `90s`, `30m`, `2h` or `1.5d`; a plain number
+/// is taken to be in seconds.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit() && c != '.') {
+        Some(i) => value.split_at(i),
+        None => (value, "s"),
+    };
+    let unit_secs = match unit {
+        "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        "d" => 86400.0,
+        _ => return None,
+    };
+    let secs = number.parse::<f64>().ok()? * unit_secs;
+
+    // keep to spans that an `Instant` can be pushed out by
+    (secs > 0.0 && secs <= u32::MAX as f64).then(|| Duration::from_secs_f64(secs))
+}
+
+/// Parse a size such as `500K`, `100M` or `1G`, in units of 1024 bytes; a
+/// plain number is taken to be in bytes.
+fn parse_size(value: &str) -> Option<u64> {
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => value.split_at(i),
+        None => (value, "B"),
+    };
+    let unit_bytes: u64 = match unit.to_ascii_uppercase().as_str() {
+        "B" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        _ => return None,
+    };
+    let size = number.parse::<u64>().ok()?.checked_mul(unit_bytes)?;
+    (size > 0).then_some(size)
+}
+
+impl Config {
+    /// Whether the search runs on the CPU rather than with OpenCL.
+    pub fn is_cpu(&self) -> bool {
+        self.gpu_devices[0].is_cpu()
+    }
+
+    /// The width of the caller segment at the start of the salt: 20 bytes, or
+    /// none with `--no-caller-salt`.
+    pub fn caller_salt_bytes(&self) -> usize {
+        if self.use_caller_salt {
+            CALLER_BYTES
+        } else {
+            0
+        }
+    }
+
+    /// The message hashed for each salt with the given init code hash.
+    pub fn preimage(&self, init_code_hash: &[u8; 32]) -> Preimage {
+        Preimage::new(&self.factory_address, init_code_hash)
+            .control_character(self.control_character)
+    }
+}
+
+/// Given a Config object with a factory address, a caller address, and a
+/// keccak-256 hash of the contract initialization code, search for salts that
+/// will enable the factory contract to deploy a contract to a gas-efficient
+/// address via CREATE2.
+///
+/// The 32-byte salt is constructed as follows:
 ///   - the 20-byte calling address (to prevent frontrunning)
 ///   - a random 6-byte segment (to prevent collisions with other runs)
 ///   - a 6-byte nonce segment (incrementally stepped through during the run)
 ///
+/// With `--no-caller-salt`, the random segment takes the place of the calling
+/// address as well, and is 26 bytes long.
+///
+/// When several callers are given, each random segment is used with the next
+/// caller in turn.
+///
 /// When a salt that will result in the creation of a gas-efficient contract
-/// address is found, it will be appended to `efficient_addresses.txt` along
-/// with the resultant address and the "value" (i.e. approximate rarity) of the
-/// resultant address.
-pub fn cpu(config: Config) -> Result<(), Box<dyn Error>> {
-    // (create if necessary) and open a file where found salts will be written
-    let file = output_file();
+/// address is found, it will be appended to the results file (by default
+/// `efficient_addresses.txt`) along with the resultant address and the "value"
+/// (i.e. approximate rarity) of the resultant address.
+pub fn cpu(config: Config) -> Result<(), Create2Error> {
+    cpu_search(config, &AtomicBool::new(false), &stop_flag())
+}
+
+/// Run the CPU search like [`cpu`], but send every solution found through `tx`
+/// instead of writing it to the results file and printing it, for callers that
+/// embed the search and handle solutions themselves.
+///
+/// This only returns once the search has reached its goal (with `--beat` or
+/// `--max-results`), or when the receiving end of the channel is dropped.
+pub fn cpu_with_sink(config: Config, tx: Sender<FoundSalt>) -> Result<(), Create2Error> {
+    let rewards = load_rewards(&config).map_err(Create2Error::Search)?;
+    let pool = cpu_pool(&config)?;
+    let trace = config.trace_path.as_deref().map(Trace::new).transpose()?;
+    let hashes = AtomicU64::new(0);
+    let stop = stop_flag();
+    in_cpu_pool(pool.as_ref(), || {
+        let paused = AtomicBool::new(false);
+        find_salts(
+            &config,
+            &rewards,
+            trace.as_ref(),
+            &paused,
+            &stop,
+            &hashes,
+            tx,
+        )
+    });
+    Ok(())
+}
+
+/// Run the CPU search on a background thread and yield each solution as it is
+/// found, for callers that would rather handle solutions with iterator adapters
+/// (e.g. `cpu_iter(config)?.take(10)`) than with a channel of their own.
+///
+/// The iterator ends once the search has reached its goal (with `--beat` or
+/// `--max-results`). When it is dropped before then, the search stops after
+/// its current batch of work.
+pub fn cpu_iter(config: Config) -> Result<CpuIter, Create2Error> {
+    let rewards = load_rewards(&config).map_err(Create2Error::Search)?;
+    let pool = cpu_pool(&config)?;
+    let trace = config.trace_path.as_deref().map(Trace::new).transpose()?;
+    let (tx, rx) = mpsc::sync_channel(CPU_ITER_BUFFER);
+    let stop = stop_flag();
+    let iter = CpuIter {
+        rx,
+        stop: Arc::clone(&stop),
+    };
+    thread::spawn(move || {
+        let hashes = AtomicU64::new(0);
+        in_cpu_pool(pool.as_ref(), || {
+            let paused = AtomicBool::new(false);
+            find_salts(
+                &config,
+                &rewards,
+                trace.as_ref(),
+                &paused,
+                &stop,
+                &hashes,
+                tx,
+            )
+        });
+    });
+    Ok(iter)
+}
+
+/// The solutions of a CPU search started with [`cpu_iter`], as they are found.
+/// Dropping it stops the search.
+pub struct CpuIter {
+    rx: Receiver<FoundSalt>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Iterator for CpuIter {
+    type Item = FoundSalt;
+
+    fn next(&mut self) -> Option<FoundSalt> {
+        self.rx.recv().ok()
+    }
+}
+
+impl Drop for CpuIter {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Where [`find_salts`] sends its solutions: an unbounded channel, or a
+/// bounded one that holds off the search while its receiver catches up. It
+/// fails once nobody is listening anymore.
+trait SolutionSender: Clone + Send {
+    fn send_solution(&self, found: FoundSalt) -> Result<(), ()>;
+}
+
+impl SolutionSender for Sender<FoundSalt> {
+    fn send_solution(&self, found: FoundSalt) -> Result<(), ()> {
+        self.send(found).map_err(drop)
+    }
+}
+
+impl SolutionSender for SyncSender<FoundSalt> {
+    fn send_solution(&self, found: FoundSalt) -> Result<(), ()> {
+        self.send(found).map_err(drop)
+    }
+}
 
+/// Run the CPU search, holding off whenever `paused` is set and stopping once
+/// `stop` is, and write each solution to the results file as it arrives from
+/// the search.
+fn cpu_search(config: Config, paused: &AtomicBool, stop: &AtomicBool) -> Result<(), Create2Error> {
     // create object for computing rewards (relative rarity) for a given address
-    let rewards = Reward::new();
+    let rewards = load_rewards(&config).map_err(Create2Error::Search)?;
+    print_captured_shapes(&config, &rewards);
 
-    // begin searching for addresses
-    loop {
-        // header: 0xff ++ factory ++ caller ++ salt_random_segment (47 bytes)
-        let mut header = [0; 47];
-        header[0] = CONTROL_CHARACTER;
-        header[1..21].copy_from_slice(&config.factory_address);
-        header[21..41].copy_from_slice(&config.calling_address);
-        header[41..].copy_from_slice(&FixedBytes::<6>::random()[..]);
-
-        // create new hash object
-        let mut hash_header = Keccak::v256();
-
-        // update hash with header
-        hash_header.update(&header);
-
-        // iterate over a 6-byte nonce and compute each address
-        (0..MAX_INCREMENTER)
-            .into_par_iter() // parallelization
-            .for_each(|salt| {
-                let salt = salt.to_le_bytes();
-                let salt_incremented_segment = &salt[..6];
-
-                // clone the partially-hashed object
-                let mut hash = hash_header.clone();
-
-                // update with body and footer (total: 38 bytes)
-                hash.update(salt_incremented_segment);
-                hash.update(&config.init_code_hash);
-
-                // hash the payload and get the result
-                let mut res: [u8; 32] = [0; 32];
-                hash.finalize(&mut res);
-
-                // get the address that results from the hash
-                let address = <&Address>::try_from(&res[12..]).unwrap();
-
-                // count total and leading zero bytes
-                let mut total = 0;
-                let mut leading = 21;
-                for (i, &b) in address.iter().enumerate() {
-                    if b == 0 {
-                        total += 1;
-                    } else if leading == 21 {
-                        // set leading on finding non-zero byte
-                        leading = i;
+    // optionally search with fewer threads than there are cores
+    let pool = cpu_pool(&config)?;
+
+    // (create if necessary) and open a file where found salts will be written
+    let results = result_writer(&config)?;
+
+    // optionally keep a separate log of the milestone finds of this run
+    let highlights = config.highlights_path.as_deref();
+    let highlights = highlights.map(Highlights::new).transpose()?;
+
+    // optionally log the coverage of the search
+    let trace = config.trace_path.as_deref().map(Trace::new).transpose()?;
+
+    // only the first solution of the run is copied to the clipboard
+    #[cfg(feature = "clipboard")]
+    let mut copied = false;
+
+    // track how many addresses have been found and for how long
+    let start = Instant::now();
+    let mut found_count: u64 = 0;
+
+    // with --best, the best solution so far
+    let mut best: Option<BestFound> = None;
+
+    // show the progress of the search on a status line below the solutions,
+    // or log it once in a while when asked to or not writing to a terminal
+    let term = status_term(&config);
+    let log_lines = config.quiet || config.no_file || !redraws_in_place(&term);
+    let hashes = AtomicU64::new(0);
+    let targets = config.callers.len() * config.init_code_hashes.len();
+    let mut rate = RollingRate::new(RATE_WINDOW);
+    rate.update(start, 0);
+    let mut previous_time = start;
+    let mut last_progress_log = start;
+    let mut status = Ok(());
+
+    // search on a separate thread and handle solutions as they come in; the
+    // channel closes once the search ends
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            in_cpu_pool(pool.as_ref(), || {
+                find_salts(&config, &rewards, trace.as_ref(), paused, stop, &hashes, tx)
+            })
+        });
+
+        loop {
+            // write out any results held back for long enough
+            results.flush_due();
+
+            // we don't want to print faster than the configured refresh interval
+            let print_output = !paused.load(Ordering::Relaxed)
+                && previous_time.elapsed() >= config.refresh_interval;
+            if print_output && status.is_ok() {
+                previous_time = Instant::now();
+                let hashed = hashes.load(Ordering::Relaxed);
+                let hashes_per_sec = rate.update(previous_time, hashed);
+                // the segments of all targets are searched through side by
+                // side, and all but the last of them in full
+                let round = (MAX_INCREMENTER as u128 + 1) * targets as u128;
+                let segment = (hashed as u128 % round) as f64 / round as f64;
+                let line = progress_line(start.elapsed(), hashes_per_sec, found_count, segment);
+                if !log_lines {
+                    status = term.clear_line().and_then(|_| term.write_str(&line));
+                } else if last_progress_log.elapsed() >= PROGRESS_LOG_INTERVAL {
+                    last_progress_log = previous_time;
+                    status = term.write_line(&line);
+                }
+            }
+
+            let found = match rx.recv_timeout(config.refresh_interval) {
+                Ok(found) => found,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            let address = &found.address;
+            let score = found.score;
+
+            // get the full salt used to create the address
+            let full_salt = format!("0x{}", hex::encode(found.salt));
+
+            // display the salt and the address.
+            let shown = display_address(address, config.checksum_addresses);
+            let reward = &found.reward;
+            let description = rewards.describe(score.leading, score.total);
+            let mut output = format!("{full_salt} => {shown} => {reward} => {description}");
+
+            // with several callers, say which one the salt is for
+            if config.callers.len() > 1 {
+                write!(output, " => caller 0x{}", hex::encode(&found.salt[..20])).unwrap();
+            }
+
+            // with several init code hashes, say which one the address is for
+            if config.init_code_hashes.len() > 1 {
+                let init_code_hash = hex::encode(found.init_code_hash);
+                write!(output, " => init code hash 0x{init_code_hash}").unwrap();
+            }
+
+            // in prefix mode, note how many nibbles were matched
+            if let Some(prefix) = &config.prefix {
+                write!(output, " => prefix {}", prefix.len()).unwrap();
+            }
+
+            // in pattern mode, note which bytes were matched where
+            if let Some((offset, pattern)) = &config.pattern {
+                write!(output, " => pattern 0x{} at {offset}", hex::encode(pattern)).unwrap();
+            }
+
+            // in repeat mode, note how long the longest repeat is
+            if config.min_repeat.is_some() {
+                write!(output, " => repeat {}", repeat_score(address).longest()).unwrap();
+            }
+
+            // in mask mode, note how many bytes were matched
+            if let Some(mask) = &config.mask {
+                write!(output, " => mask {}", mask.len()).unwrap();
+            }
+
+            // optionally note the gas the address is estimated to save
+            if config.show_gas {
+                write!(output, " => {}", gas_savings(score.leading, score.total)).unwrap();
+            }
+
+            // optionally append the full preimage that was hashed
+            if config.show_preimage {
+                let preimage = config.preimage(&found.init_code_hash).message(&found.salt);
+                write!(output, " => 0x{}", hex::encode(preimage)).unwrap();
+            }
+
+            // optionally replace the line with a JSON record, a self-contained
+            // proof bundle, or a Solidity declaration
+            match config.output_format {
+                OutputFormat::Text => {}
+                OutputFormat::Json => {
+                    let record = FoundRecord {
+                        address: shown.clone(),
+                        init_code_hash: (config.init_code_hashes.len() > 1)
+                            .then(|| format!("0x{}", hex::encode(found.init_code_hash))),
+                        ..FoundRecord::new(&found.salt, address, &score, &found.reward)
+                    };
+                    output = record.to_json();
+                }
+                OutputFormat::Bundle => {
+                    let mut bundle = ProofBundle {
+                        address: shown.clone(),
+                        ..ProofBundle::new(
+                            &config.factory_address,
+                            &found.salt,
+                            &found.init_code_hash,
+                            address,
+                        )
+                    };
+                    // without a caller segment, the salt isn't for any caller
+                    if !config.use_caller_salt {
+                        bundle.caller = None;
                     }
+                    output = bundle.to_json();
+                }
+                OutputFormat::Solidity => {
+                    let init_code_hash =
+                        (config.init_code_hashes.len() > 1).then_some(&found.init_code_hash);
+                    output = solidity_declaration(&found.salt, address, init_code_hash);
                 }
+            }
+
+            // optionally show how the address looks when truncated by wallets
+            let mut show = output.clone();
+            if config.show_preview {
+                write!(show, " [{}]", truncated_display(address)).unwrap();
+            }
+
+            // with --best, only keep the solution if it is the best so far
+            if config.best {
+                let candidate = BestFound {
+                    reward: found.reward.parse().unwrap_or(0.0),
+                    leading: score.leading,
+                    output,
+                    show,
+                };
+                if best.as_ref().map_or(true, |best| candidate.beats(best)) {
+                    best = Some(candidate);
+                }
+                found_count += 1;
+                continue;
+            }
+
+            // the status line makes way for the solution, and is redrawn below it
+            if !log_lines && status.is_ok() {
+                status = term.clear_line();
+            }
 
-                // only proceed if there are at least three zero bytes
-                if total < 3 {
-                    return;
+            // when waiting for an interesting address, stay silent until one
+            // turns up and then make it stand out
+            match config.quiet_until {
+                Some(until) if score.leading >= until as usize => {
+                    let leading = score.leading;
+                    status!(
+                        config,
+                        "*** found an address with {leading} leading zero bytes ***"
+                    );
+                    status!(config, "{show}");
                 }
+                Some(_) => {}
+                None => status!(config, "{show}"),
+            }
+
+            #[cfg(feature = "clipboard")]
+            if config.copy_to_clipboard && !copied {
+                copied = true;
+                clipboard::copy_to_clipboard(&format!("{full_salt}\n{shown}"));
+            }
+
+            #[cfg(feature = "hooks")]
+            if let Some(command) = &config.on_found_command {
+                let (leading, total) = (score.leading, score.total);
+                hooks::run_on_found(command, &full_salt, &shown, leading, total, config.no_file);
+            }
+
+            // write the result to file
+            results.write(score.leading, &output);
+            if let Some(highlights) = &highlights {
+                highlights.record(&score, &output);
+            }
+            found_count += 1;
+        }
+    });
+    if !log_lines && status.is_ok() {
+        status = term.clear_line();
+    }
+    status?;
+    write_best(&config, &results, best);
+
+    // otherwise, the search only ends once it has run out of time, found as
+    // many addresses as requested, or beaten the reference address
+    results.flush();
+    if stop.load(Ordering::Relaxed) {
+        print_summary(&config, found_count, start.elapsed());
+    } else if config.max_results.map_or(false, |max| found_count >= max) {
+        status!(
+            config,
+            "Found {found_count} addresses, stopping as requested."
+        );
+    } else if config
+        .max_duration
+        .map_or(false, |max| start.elapsed() >= max)
+    {
+        status!(config, "Reached the time limit.");
+        print_summary(&config, found_count, start.elapsed());
+    } else {
+        status!(config, "Found an address that beats the reference address.");
+    }
+    if config.sort_on_exit {
+        results.sort();
+    }
+    Ok(())
+}
+
+/// Build a pool of `--cpu-threads` threads for the CPU search, if a number was
+/// given; the search runs on rayon's global pool otherwise.
+fn cpu_pool(config: &Config) -> Result<Option<ThreadPool>, Create2Error> {
+    config
+        .cpu_threads
+        .map(|threads| ThreadPoolBuilder::new().num_threads(threads).build())
+        .transpose()
+        .map_err(|e| Create2Error::Search(format!("couldn't start the CPU threads: {e}")))
+}
+
+/// Run `search` in `pool` if there is one, so that its parallel iterators use
+/// that pool's threads, or on the global pool otherwise.
+fn in_cpu_pool<R: Send>(pool: Option<&ThreadPool>, search: impl FnOnce() -> R + Send) -> R {
+    match pool {
+        Some(pool) => pool.install(search),
+        None => search(),
+    }
+}
+
+/// The CPU search itself: send every solution through `tx` until the search
+/// reaches its goal, `stop` is set, it runs out of time, or `tx` is
+/// disconnected, holding off whenever `paused` is set, counting the salts
+/// hashed in `hashes`, and logging the coverage of the search to `trace`.
+fn find_salts(
+    config: &Config,
+    rewards: &Reward,
+    trace: Option<&Trace>,
+    paused: &AtomicBool,
+    stop: &AtomicBool,
+    hashes: &AtomicU64,
+    tx: impl SolutionSender,
+) {
+    // optionally stop searching after a while
+    let deadline = config
+        .max_duration
+        .map(|duration| Instant::now() + duration);
+
+    // when trying to beat a reference address, score it up front
+    let beat_score = config.beat_address.as_ref().map(score_address);
+
+    // when looking for small addresses, track the smallest one found so far
+    let smallest = config.max_address.map(Mutex::new);
+
+    // count the solutions sent, to stop at the requested number
+    let sent = AtomicU64::new(0);
+
+    // every caller with the first init code hash, and then with the next one
+    let targets: Vec<_> = config
+        .init_code_hashes
+        .iter()
+        .flat_map(|init_code_hash| {
+            config
+                .callers
+                .iter()
+                .map(move |caller| (caller, init_code_hash))
+        })
+        .collect();
+
+    // with a seed, the first segment is the seed and each further one counts
+    // up from it, so that the run can be reproduced
+    let mut seeded_segment = config.salt_seed.as_ref().map(|seed| {
+        let mut segment = [0; 8];
+        segment[2..].copy_from_slice(&seed[seed.len() - 6..]);
+        u64::from_be_bytes(segment)
+    });
 
-                // look up the reward amount
-                let key = leading * 20 + total;
-                let reward_amount = rewards.get(&key);
+    // the random segment takes up the caller segment too if there is none
+    let random_start = config.caller_salt_bytes();
 
-                // only proceed if an efficient address has been found
-                if reward_amount.is_none() {
-                    return;
+    // begin searching for addresses
+    loop {
+        // give each target a segment of its own: the head of the salt is
+        // caller ++ salt_random_segment (26 bytes)
+        let segments: Vec<_> = targets
+            .iter()
+            .map(|&(caller, init_code_hash)| {
+                let mut salt_head = [0; Preimage::SALT_HEAD_LEN];
+                match (config.use_caller_salt, &config.salt_seed) {
+                    (true, _) => salt_head[..20].copy_from_slice(caller),
+                    (false, Some(seed)) => salt_head[..20].copy_from_slice(&seed[..20]),
+                    (false, None) => {
+                        salt_head[..20].copy_from_slice(&FixedBytes::<20>::random()[..])
+                    }
+                }
+                match seeded_segment.as_mut() {
+                    Some(segment) => {
+                        salt_head[20..].copy_from_slice(&segment.to_be_bytes()[2..]);
+                        *segment = (*segment + 1) & MAX_INCREMENTER;
+                    }
+                    None => salt_head[20..].copy_from_slice(&FixedBytes::<6>::random()[..]),
                 }
+                (caller, init_code_hash, salt_head)
+            })
+            .collect();
+
+        // take turns searching a chunk of the nonces of each segment, so that
+        // every target is searched long before a whole segment is
+        for (target, nonces) in target_chunks(segments.len()) {
+            let (caller, init_code_hash, salt_head) = segments[target];
+
+            // hash the header, i.e. the message up to the end of the head of
+            // the salt, once for every salt that shares it
+            let hasher = config.preimage(init_code_hash).hasher(&salt_head);
+
+            // iterate over every value of the 6-byte nonce in the chunk and
+            // compute each address, stopping early once the search has reached
+            // its goal, has been asked to stop, has run out of time, or nobody
+            // is listening anymore; otherwise, the whole chunk is searched
+            // before moving on
+            let stopped = nonces
+                .clone()
+                .into_par_iter() // parallelization
+                .try_for_each_with(tx.clone(), |tx, salt| {
+                    // hold off while the search is paused, unless it is to stop
+                    while paused.load(Ordering::Relaxed) && !stop.load(Ordering::Relaxed) {
+                        std::thread::sleep(PAUSE_POLL_INTERVAL);
+                    }
+
+                    // a single random segment takes far too long to search to only
+                    // check for a stop request or the deadline between segments;
+                    // the clock is only read once in a batch of salts, as reading
+                    // it takes a good fraction of the time needed to hash one
+                    if stop.load(Ordering::Relaxed) {
+                        return Err(());
+                    }
+                    if let Some(deadline) = deadline {
+                        if salt & 0xffff == 0 && Instant::now() >= deadline {
+                            return Err(());
+                        }
+                    }
+
+                    // the salts hashed are counted a batch at a time as well
+                    if salt & 0xffff == 0 {
+                        hashes.fetch_add(0x10000, Ordering::Relaxed);
+                    }
+
+                    let salt = salt.to_le_bytes();
+                    let salt_incremented_segment = &salt[..6];
+
+                    // get the address that results from the salt
+                    let address = hasher.address(salt_incremented_segment);
+
+                    // only proceed if the address meets the search criteria
+                    let Some(Match {
+                        score,
+                        reward: reward_amount,
+                    }) = evaluate_address(&address, rewards, config, beat_score.as_ref())
+                    else {
+                        return Ok(());
+                    };
+
+                    // when looking for small addresses, only keep one that is
+                    // smaller than any found so far
+                    if let Some(smallest) = &smallest {
+                        let mut smallest = smallest.lock().unwrap();
+                        if !is_below(&address, &smallest) {
+                            return Ok(());
+                        }
+                        *smallest = address.into();
+                    }
+
+                    // get the full salt used to create the address
+                    let mut full_salt = [0; 32];
+                    full_salt[..26].copy_from_slice(&salt_head);
+                    full_salt[26..].copy_from_slice(salt_incremented_segment);
+
+                    // other threads may have found the last of the requested
+                    // solutions in the meantime
+                    let count = sent.fetch_add(1, Ordering::Relaxed) + 1;
+                    if config.max_results.map_or(false, |max| count > max) {
+                        return Err(());
+                    }
+
+                    let found = FoundSalt {
+                        salt: full_salt,
+                        init_code_hash: *init_code_hash,
+                        address,
+                        score,
+                        reward: reward_amount.unwrap_or("0").to_string(),
+                    };
+                    tx.send_solution(found)?;
+
+                    // a single address that beats the reference is all we need,
+                    // and no more than the requested number of addresses
+                    if beat_score.is_some() || config.max_results == Some(count) {
+                        return Err(());
+                    }
+
+                    Ok(())
+                })
+                .is_err();
+
+            // nonces are searched in parallel, so the coverage of a chunk that
+            // was cut short is unknown and isn't traced
+            if stopped {
+                return;
+            }
+            if let Some(trace) = trace {
+                trace.record(caller, &salt_head[random_start..], nonces);
+            }
+        }
+    }
+}
+
+/// Given a Config object with a factory address, a caller address, a keccak-256
+/// hash of the contract initialization code, and a device ID, search for salts
+/// using OpenCL that will enable the factory contract to deploy a contract to a
+/// gas-efficient address via CREATE2. This method also takes threshold values
+/// for both leading zero bytes and total zero bytes - any address that does not
+/// meet or exceed the threshold will not be returned. Default threshold values
+/// are three leading zeroes or five total zeroes.
+///
+/// The 32-byte salt is constructed as follows:
+///   - the 20-byte calling address (to prevent frontrunning)
+///   - a random segment (to prevent collisions with other runs)
+///   - a 4-byte segment unique to each work group running in parallel
+///   - a nonce segment (incrementally stepped through during the run)
+///
+/// The random and nonce segments share eight bytes, split 4:4 by default
+/// (see `Config::salt_random_bytes` and `Config::salt_nonce_bytes`). With
+/// `--no-caller-salt`, there is no calling address and they share 28 bytes,
+/// split 24:4 by default.
+///
+/// When several devices are given, each searches on its own thread with its
+/// own random segments, and the dashboard shows their combined rate.
+///
+/// When a salt that will result in the creation of a gas-efficient contract
+/// address is found, it will be appended to the results file (by default
+/// `efficient_addresses.txt`) along with the resultant address and the "value"
+/// (i.e. approximate rarity) of the resultant address.
+///
+/// This method is still highly experimental and could almost certainly use
+/// further optimization - contributions are more than welcome!
+pub fn gpu(config: Config) -> Result<(), Create2Error> {
+    gpu_search(config, &AtomicBool::new(false), &stop_flag())
+}
+
+/// Run the OpenCL search on each configured device in parallel, holding off
+/// between cycles whenever `paused` is set and stopping once `stop` is.
+fn gpu_search(config: Config, paused: &AtomicBool, stop: &AtomicBool) -> Result<(), Create2Error> {
+    let devices: Vec<String> = config.gpu_devices.iter().map(ToString::to_string).collect();
+    status!(
+        config,
+        "Setting up experimental OpenCL miner using device {}...",
+        devices.join(", ")
+    );
+
+    let search = GpuSearch::new(&config)?;
+    print_captured_shapes(&config, &search.rewards);
+
+    // optionally expose the counters of the search for Prometheus to scrape
+    let metrics_listener = match config.metrics_port {
+        Some(port) => {
+            let addr = config.metrics_addr;
+            Some(TcpListener::bind((addr, port)).map_err(|e| {
+                Create2Error::Search(format!("couldn't serve metrics on {addr} port {port}: {e}"))
+            })?)
+        }
+        None => None,
+    };
+
+    // set up a controller for terminal output
+    let term = status_term(&config);
+
+    // determine the start time
+    let start_time = Instant::now();
+
+    // search with every device on its own thread, and redraw the dashboard
+    // from this one until all of them are done
+    let outcome: Result<(), Create2Error> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..config.gpu_devices.len())
+            .map(|index| {
+                let search = &search;
+                scope.spawn(move || {
+                    let result = gpu_worker(search, index, paused, stop);
+
+                    // a device failing or reaching the goal ends the search
+                    search.finished.store(true, Ordering::Relaxed);
+                    result
+                })
+            })
+            .collect();
+
+        // answer metrics requests on a thread of their own until the search ends
+        if let Some(listener) = &metrics_listener {
+            let search = &search;
+            scope.spawn(move || metrics::serve(listener, &search.finished, || search.metrics()));
+        }
+
+        // the previous time of printing to the terminal, leaving the output of
+        // setting up the devices on screen for a moment
+        let mut previous_time = Instant::now();
+
+        // measure the rate over the last few seconds rather than the whole run
+        let mut rate = RollingRate::new(RATE_WINDOW);
+        rate.update(start_time, 0);
+
+        // keep the status file up to date, warning only the first time it
+        // can't be written
+        let mut status_failed = false;
+        let mut save_status = |state, hashes_per_sec| {
+            let Some(path) = &config.status_path else {
+                return;
+            };
+            let status = search.status(state, start_time.elapsed(), hashes_per_sec);
+            if let Err(e) = status.save(path) {
+                if !status_failed {
+                    status!(
+                        config,
+                        "Warning: couldn't write the status to `{}`: {e}",
+                        path.display()
+                    );
+                }
+                status_failed = true;
+            }
+        };
+
+        let mut dashboard = Ok(());
+        let mut last_progress_log = Instant::now();
+        let mut was_paused = false;
+        while !workers.iter().all(|worker| worker.is_finished()) {
+            // write out any results held back for long enough
+            search.results.flush_due();
+
+            // the status isn't refreshed while paused, so note the pause in it
+            let is_paused = paused.load(Ordering::Relaxed);
+            if is_paused && !was_paused {
+                save_status(State::Paused, 0.0);
+            }
+            was_paused = is_paused;
+
+            // we don't want to print faster than the configured refresh interval
+            let print_output = !is_paused && previous_time.elapsed() >= config.refresh_interval;
+            if print_output && dashboard.is_ok() {
+                previous_time = Instant::now();
+                let runtime = start_time.elapsed();
+                let hashes_per_sec = rate.update(previous_time, search.attempts());
+                *search.hashes_per_sec.lock().unwrap() = hashes_per_sec;
+                save_status(State::Running, hashes_per_sec);
+
+                // when logging, only note the progress once in a while
+                if !search.log_lines {
+                    dashboard = search.draw(&term, runtime, hashes_per_sec);
+                } else if last_progress_log.elapsed() >= PROGRESS_LOG_INTERVAL {
+                    last_progress_log = Instant::now();
+                    dashboard = search.log_progress(&term, runtime, hashes_per_sec);
+                }
+                if dashboard.is_err() {
+                    search.finished.store(true, Ordering::Relaxed);
+                }
+            }
+            std::thread::sleep(PAUSE_POLL_INTERVAL.min(config.refresh_interval));
+        }
+        save_status(State::Finished, 0.0);
+
+        // report the first device that failed, if any
+        for worker in workers {
+            worker
+                .join()
+                .unwrap_or_else(|e| std::panic::resume_unwind(e))?;
+        }
+        Ok(dashboard?)
+    });
+    outcome?;
+
+    if search.quiet.load(Ordering::Relaxed) {
+        term.write_line("")?;
+    }
+    write_best(&config, &search.results, search.best.lock().unwrap().take());
+
+    // when asked to stop, make sure every solution has been written out;
+    // otherwise the search only ends once it has run out of time, found as
+    // many addresses as requested, or beaten the reference address
+    let found = search.found.load(Ordering::Relaxed);
+    search.results.flush();
+    if stop.load(Ordering::Relaxed) {
+        print_summary(&config, found, start_time.elapsed());
+    } else if config.max_results.map_or(false, |max| found >= max) {
+        status!(config, "Found {found} addresses, stopping as requested.");
+    } else if search.out_of_time() {
+        status!(config, "Reached the time limit.");
+        print_summary(&config, found, start_time.elapsed());
+    } else {
+        if let Some(show) = search.found_list.lock().unwrap().last() {
+            status!(config, "{show}");
+        }
+        status!(config, "Found an address that beats the reference address.");
+    }
+    if config.sort_on_exit {
+        search.results.sort();
+    }
+    Ok(())
+}
+
+/// The state of an OpenCL search that is shared by the threads searching with
+/// each device.
+struct GpuSearch<'a> {
+    config: &'a Config,
+    results: BufferedResults,
+    seen: SeenSalts,
+    highlights: Option<Highlights>,
+    trace: Option<Trace>,
+    rewards: Reward,
+    beat_score: Option<Score>,
+    smallest: Mutex<Option<[u8; 20]>>,
+    found: AtomicU64,
+    found_list: Mutex<Vec<String>>,
+    best: Mutex<Option<BestFound>>,
+    #[cfg(feature = "clipboard")]
+    copied: AtomicBool,
+    rejected: AtomicU64,
+    last_mismatch: Mutex<Option<String>>,
+    hashes_per_sec: Mutex<f64>,
+    match_probability: Option<f64>,
+    log_lines: bool,
+    quiet: AtomicBool,
+    deadline: Option<Instant>,
+    finished: AtomicBool,
+    progress: Vec<Mutex<DeviceProgress>>,
+}
+
+/// What a device is currently searching, and how far it has got, as shown on
+/// the dashboard.
+#[derive(Default)]
+struct DeviceProgress {
+    caller: [u8; 20],
+    salt: Vec<u8>,
+    nonce: u64,
+    cycles: u64,
+    attempts: u64,
+    work_size: u64,
+    work_duration_millis: u64,
+    segments_used: u64,
+    segment_progress: f64,
+}
+
+impl<'a> GpuSearch<'a> {
+    fn new(config: &'a Config) -> Result<Self, Create2Error> {
+        // log plain lines rather than redraw the dashboard when asked to, or
+        // when the output doesn't go to a terminal that it can be redrawn on
+        let log_lines = config.quiet || config.no_file || !redraws_in_place(&status_term(config));
+
+        let highlights = config.highlights_path.as_deref();
+
+        Ok(Self {
+            config,
+
+            // (create if necessary) and open a file where found salts will be written
+            results: result_writer(config)?,
+
+            // remember which salts have been written, to skip any reported twice
+            seen: SeenSalts::new(),
+
+            // optionally keep a separate log of the milestone finds of this run
+            highlights: highlights.map(Highlights::new).transpose()?,
+
+            // optionally log the coverage of the search
+            trace: config.trace_path.as_deref().map(Trace::new).transpose()?,
+
+            // create object for computing rewards (relative rarity) for a given address
+            rewards: load_rewards(config).map_err(Create2Error::Search)?,
+
+            // when trying to beat a reference address, score it up front
+            beat_score: config.beat_address.as_ref().map(score_address),
+
+            // when looking for small addresses, track the smallest one found so far
+            smallest: Mutex::new(config.max_address),
+
+            // track how many addresses have been found and information about them
+            found: AtomicU64::new(0),
+            found_list: Mutex::new(vec![]),
+            best: Mutex::new(None),
+
+            // only the first solution of the run, on any device, is copied to
+            // the clipboard
+            #[cfg(feature = "clipboard")]
+            copied: AtomicBool::new(false),
+
+            // track how many reported solutions failed verification on the
+            // host, apart from the solutions found
+            rejected: AtomicU64::new(0),
+            last_mismatch: Mutex::new(None),
+
+            // the rate as of the last dashboard refresh, for the metrics
+            hashes_per_sec: Mutex::new(0.0),
+
+            // the chance of each hash being a match, to estimate the time to
+            // the next one
+            match_probability: match_probability(config),
+
+            log_lines,
+
+            // show only a heartbeat line until an interesting address is found
+            quiet: AtomicBool::new(config.quiet_until.is_some() && !log_lines),
+
+            // optionally stop searching after a while
+            deadline: config
+                .max_duration
+                .map(|duration| Instant::now() + duration),
+
+            finished: AtomicBool::new(false),
+            progress: config
+                .gpu_devices
+                .iter()
+                .map(|_| Mutex::default())
+                .collect(),
+        })
+    }
+
+    /// Whether the search has run for as long as it was allowed to.
+    fn out_of_time(&self) -> bool {
+        self.deadline
+            .map_or(false, |deadline| Instant::now() >= deadline)
+    }
+
+    /// The number of attempts made so far by all devices.
+    fn attempts(&self) -> u64 {
+        self.progress
+            .iter()
+            .map(|p| p.lock().unwrap().attempts)
+            .sum()
+    }
+
+    /// The share of their current segments searched so far, averaged over
+    /// all devices.
+    fn segment_progress(&self) -> f64 {
+        let progress = self.progress.iter().map(|p| p.lock().unwrap());
+        progress.map(|p| p.segment_progress).sum::<f64>() / self.progress.len() as f64
+    }
+
+    /// A snapshot of the search for the status file.
+    fn status(&self, state: State, runtime: Duration, hashes_per_sec: f64) -> Status {
+        Status {
+            state,
+            runtime_secs: runtime.as_secs_f64(),
+            cumulative_nonce: self.progress.iter().map(|p| p.lock().unwrap().cycles).sum(),
+            hashes_per_sec,
+            found: self.found.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The counters of the search for the metrics endpoint.
+    fn metrics(&self) -> Metrics {
+        Metrics {
+            hashes: self.attempts(),
+            found: self.found.load(Ordering::Relaxed),
+            hashes_per_sec: *self.hashes_per_sec.lock().unwrap(),
+        }
+    }
+
+    /// Log a line with the runtime, rate, number found and segment progress
+    /// so far, given the current rate in attempts per second.
+    fn log_progress(&self, term: &Term, runtime: Duration, rate: f64) -> std::io::Result<()> {
+        let found = self.found.load(Ordering::Relaxed);
+        let segment = self.segment_progress();
+        term.write_line(&progress_line(runtime, rate, found, segment))
+    }
+
+    /// Redraw the dashboard, or the heartbeat line while quiet, given the
+    /// current rate in attempts per second.
+    fn draw(&self, term: &Term, runtime: Duration, rate: f64) -> std::io::Result<()> {
+        let config = self.config;
+        let progress: Vec<_> = self.progress.iter().map(|p| p.lock().unwrap()).collect();
+        let found = self.found.load(Ordering::Relaxed);
+
+        // get the total runtime and parse into hours : minutes : seconds
+        let total_runtime = runtime.as_secs_f64();
+        let total_runtime_hrs = total_runtime as u64 / 3600;
+        let total_runtime_mins = (total_runtime as u64 - total_runtime_hrs * 3600) / 60;
+        let total_runtime_secs =
+            total_runtime - (total_runtime_hrs * 3600) as f64 - (total_runtime_mins * 60) as f64;
+
+        // show the number of attempts being made per second by all devices in millions
+        let rate = rate / 1_000_000.0;
+
+        // while quiet, overwrite a single heartbeat line in place
+        if self.quiet.load(Ordering::Relaxed) {
+            term.clear_line()?;
+            return term.write_str(&format!(
+                "{}:{:02}:{:02} - {:.2} million attempts per second - {} found, \
+                 waiting for {} leading zero bytes",
+                total_runtime_hrs,
+                total_runtime_mins,
+                total_runtime_secs as u64,
+                rate,
+                found,
+                config.quiet_until.unwrap_or_default(),
+            ));
+        }
+
+        // clear the terminal screen
+        term.clear_screen()?;
+
+        // calculate the terminal height, defaulting to a height of ten rows
+        // where it can't be told; some Windows consoles report a height of zero
+        let height = terminal_size()
+            .map(|(_w, Height(h))| h)
+            .filter(|&h| h > 0)
+            .unwrap_or(10);
+
+        // display information about the total runtime and work size
+        let cycles: u64 = progress.iter().map(|p| p.cycles).sum();
+        let work_size: u64 = progress.iter().map(|p| p.work_size).sum();
+        let durations: Vec<String> = progress
+            .iter()
+            .map(|p| p.work_duration_millis.to_string())
+            .collect();
+
+        // the work size that would bring the kernel time of each device close
+        // to the target
+        let suggested: Vec<String> = progress
+            .iter()
+            .map(|p| {
+                suggested_work_size(p.work_size, p.work_duration_millis, TARGET_CYCLE_DURATION)
+            })
+            .map(|work_size| work_size.map_or("-".to_string(), |size| format!("{size:#x}")))
+            .collect();
+        term.write_line(&format!(
+            "total runtime: {}:{:02}:{:02} ({} cycles, {} ms per cycle)\t\t\
+             work size per cycle: {} (8x vectorized; {} suggested for ~{} ms cycles)",
+            total_runtime_hrs,
+            total_runtime_mins,
+            total_runtime_secs,
+            cycles,
+            durations.join(" / "),
+            work_size.separated_string(),
+            suggested.join(" / "),
+            TARGET_CYCLE_DURATION.as_millis(),
+        ))?;
+
+        // estimate how long the next match will take once the rate has been
+        // measured over a full window
+        let expected = match self.match_probability {
+            Some(probability) if runtime >= RATE_WINDOW && rate > 0.0 => {
+                let secs = 1.0 / (probability * rate * 1_000_000.0);
+                format!("expected {} for next match", describe_expected_time(secs))
+            }
+            _ => "expected time for next match unknown".to_string(),
+        };
+
+        // display information about the attempt rate and found solutions
+        term.write_line(&format!(
+            "rate: {:.2} million attempts per second\t\t\t\
+             total found this run: {}\t\t\
+             rejected (hardware error?): {}\t\t\
+             {}",
+            rate,
+            found,
+            self.rejected.load(Ordering::Relaxed),
+            expected,
+        ))?;
+
+        // display information about the current search criteria
+        let smallest = *self.smallest.lock().unwrap();
+        let threshold = match (config.min_zero_run, smallest) {
+            (Some(run), _) => format!("run of {run} zero bytes"),
+            (None, Some(bound)) => format!("below 0x{}", hex::encode(bound)),
+            (None, None) if config.prefix.is_some() => {
+                let prefix = config.prefix.as_deref().unwrap_or_default();
+                let digits: String = prefix.iter().map(|n| format!("{n:x}")).collect();
+                format!("prefix 0x{digits}")
+            }
+            (None, None) if config.pattern.is_some() => {
+                let (offset, pattern) = config.pattern.as_ref().unwrap();
+                format!("pattern 0x{} at byte {offset}", hex::encode(pattern))
+            }
+            (None, None) if config.min_repeat.is_some() => {
+                let min_repeat = config.min_repeat.unwrap_or_default();
+                format!("repeat of {min_repeat} bytes")
+            }
+            (None, None) if config.mask.is_some() => {
+                let mask = config.mask.as_deref().unwrap_or_default();
+                format!("mask of {} bytes", mask.len())
+            }
+            (None, None) => {
+                let unit = match config.zero_granularity {
+                    Granularity::Byte => "",
+                    Granularity::Nibble => " nibble",
+                };
+                format!(
+                    "{}{unit} leading or {} total zeroes",
+                    config.leading_zeroes_threshold, config.total_zeroes_threshold
+                )
+            }
+        };
+        let nonce_bytes = config.salt_nonce_bytes as usize;
+        for (selector, progress) in config.gpu_devices.iter().zip(&progress) {
+            // with several devices, say which one is searching where
+            let device = match config.gpu_devices.len() {
+                1 => String::new(),
+                _ => format!("[{selector}] "),
+            };
+            // with several callers, also show which one is being searched
+            let caller_hex = match config.callers.len() {
+                1 => String::new(),
+                _ => hex::encode(progress.caller),
+            };
+            term.write_line(&format!(
+                "{device}current search space: {caller_hex}{}xxxxxxxx{}\t\t\
+                 threshold: {threshold}\t\tsegment progress: {}",
+                hex::encode(&progress.salt),
+                hex::encode(&progress.nonce.to_le_bytes()[..nonce_bytes]),
+                percent(progress.segment_progress),
+            ))?;
+        }
+
+        // warn when random segments may start to repeat within this run
+        let segments_used: u64 = progress.iter().map(|p| p.segments_used).sum();
+        term.write_line(&format!(
+            "random segments used: {}\t\t\t\
+             segment collision probability: {:.2e}",
+            segments_used,
+            segment_collision_probability(config.salt_random_bytes, segments_used),
+        ))?;
+
+        // display the last solution that failed verification, if any
+        let last_mismatch = self.last_mismatch.lock().unwrap();
+        if let Some(mismatch) = last_mismatch.as_ref() {
+            term.write_line(mismatch)?;
+        }
+
+        // display recently found solutions based on terminal height
+        let reserved = 4 + progress.len() + usize::from(last_mismatch.is_some());
+        let rows = if (height as usize) <= reserved {
+            1
+        } else {
+            height as usize - reserved
+        };
+        let found_list = self.found_list.lock().unwrap();
+        let last_rows: Vec<String> = found_list.iter().cloned().rev().take(rows).collect();
+        let ordered: Vec<String> = last_rows.iter().cloned().rev().collect();
+        let recently_found = &ordered.join("\n");
+        term.write_line(recently_found)
+    }
+}
+
+/// Search with the `index`-th configured device until `stop` is set or the
+/// search has ended on any device, holding off between cycles whenever
+/// `paused` is set.
+fn gpu_worker(
+    search: &GpuSearch<'_>,
+    index: usize,
+    paused: &AtomicBool,
+    stop: &AtomicBool,
+) -> ocl::Result<()> {
+    let config = search.config;
+
+    // set up a controller for terminal output
+    let term = status_term(config);
+
+    // select the device and the work sizes to use with it
+    let (platform, device) = select_device(config, &config.gpu_devices[index])?;
+    let (local_work_size, global_work_size) = work_sizes(config, device);
+
+    // set up the context to use
+    let context = Context::builder()
+        .platform(platform)
+        .devices(device)
+        .build()?;
+
+    // set up the queue to use
+    let queue = Queue::new(&context, device, None)?;
+
+    // the caller and init code hash are baked into the kernel, so set up a
+    // program and a "proqueue" (or amalgamation of various elements) for each
+    // caller with each init code hash
+    let mut pro_ques = vec![];
+    for init_code_hash in &config.init_code_hashes {
+        for caller in &config.callers {
+            let src = mk_kernel_src(config, caller, init_code_hash);
+
+            // optionally show the defines the template was populated with, to
+            // check them against the arguments
+            if config.verbose {
+                status!(
+                    config,
+                    "Kernel defines for caller 0x{} and init code hash 0x{}:",
+                    hex::encode(caller),
+                    hex::encode(init_code_hash)
+                );
+                let defines = &src[..src.len() - KERNEL_SRC.len()];
+                status!(config, "{}", defines.trim_end());
+            }
+
+            let program = build_kernel(&context, device, src)?;
+            let ocl_pq = ProQue::new(
+                context.clone(),
+                queue.clone(),
+                program,
+                Some(global_work_size),
+            );
+            pro_ques.push(ocl_pq);
+        }
+    }
+
+    // create a random number generator
+    let mut rng = thread_rng();
+
+    // set up variables for tracking performance
+    let mut cumulative_nonce: u64 = 0;
+
+    // track how many random salt segments have been used so far this run
+    let mut segments_used: u64 = 0;
+
+    // the last work duration in milliseconds
+    let mut work_duration_millis: u64 = 0;
+
+    // Create reusable buffers once to avoid memory leaks
+    let message_buffer = Buffer::builder()
+        .queue(queue.clone())
+        .flags(MemFlags::new().read_write())
+        .len(MAX_RANDOM_BYTES)
+        .build()?;
+
+    let nonce_buffer = Buffer::builder()
+        .queue(queue.clone())
+        .flags(MemFlags::new().read_write())
+        .len(1)
+        .build()?;
+
+    // Increase solutions buffer size for vectorization (64 slots)
+    let mut solutions: Vec<u64> = vec![0; SOLUTION_SLOTS as usize];
+    let solutions_buffer = Buffer::builder()
+        .queue(queue.clone())
+        .flags(MemFlags::new().write_only())
+        .len(SOLUTION_SLOTS)
+        .copy_host_slice(&solutions)
+        .build()?;
+
+    // widths of the caller, random and nonce segments of the salt
+    let caller_bytes = config.caller_salt_bytes();
+    let random_bytes = config.salt_random_bytes as usize;
+    let nonce_bytes = config.salt_nonce_bytes as usize;
+    let nonce_mask = u64::MAX >> (64 - 8 * nonce_bytes);
+
+    // with a seed, the seed is the segment searched, and each iteration
+    // carries on with the nonce after the last one searched; once the nonce
+    // segment is used up, the segment counts up from the seed; sequential
+    // salts do the same from a random segment
+    let mut seeded_segment = [0; MAX_RANDOM_BYTES];
+    match &config.salt_seed {
+        Some(seed) => seeded_segment[..random_bytes].copy_from_slice(seed),
+        None if config.sequential_salt => seeded_segment = rng.gen(),
+        None => {}
+    }
+    let counting = config.salt_seed.is_some() || config.sequential_salt;
+    let mut seeded_nonce: u64 = 0;
+
+    // the state of the search is only saved, and resumed, with a single device
+    let checkpointing = config.gpu_devices.len() == 1;
+
+    // optionally pick up where a previous run left off
+    let mut resumed = None;
+    if config.resume {
+        match Checkpoint::load(Path::new(CHECKPOINT_PATH), config) {
+            Ok(checkpoint) => {
+                cumulative_nonce = checkpoint.cumulative_nonce;
+                segments_used = checkpoint.segments_used;
+                resumed = Some(checkpoint);
+            }
+            Err(e) => status!(
+                config,
+                "Warning: couldn't resume from `{CHECKPOINT_PATH}` ({e}), starting a fresh search."
+            ),
+        }
+    }
+    let mut last_checkpoint = Instant::now();
+
+    // begin searching for addresses
+    loop {
+        let (target, salt, mut nonce) = match resumed.take() {
+            // carry on with the segment of the previous run where it left off
+            Some(checkpoint) => {
+                let caller_index = config
+                    .callers
+                    .iter()
+                    .position(|caller| *caller == checkpoint.caller)
+                    .unwrap();
+                let hash_index = config
+                    .init_code_hashes
+                    .iter()
+                    .position(|init_code_hash| *init_code_hash == checkpoint.init_code_hash)
+                    .unwrap();
+                let mut salt = [0; MAX_RANDOM_BYTES];
+                salt[..random_bytes].copy_from_slice(&checkpoint.salt);
+                let target = hash_index * config.callers.len() + caller_index;
+                (target, salt, [checkpoint.nonce])
+            }
+            None => {
+                // each random segment is used with the next caller in turn,
+                // and then with the next init code hash
+                let target = (segments_used % pro_ques.len() as u64) as usize;
+                segments_used += 1;
+
+                if counting {
+                    (target, seeded_segment, [seeded_nonce])
+                } else {
+                    // reset nonce - for more uniformly distributed nonces, we shall
+                    // initialize it to a random value that fits in the nonce segment
+                    (target, rng.gen(), [rng.gen::<u64>() & nonce_mask])
+                }
+            }
+        };
+        let caller = &config.callers[target % config.callers.len()];
+        let init_code_hash = &config.init_code_hashes[target / config.callers.len()];
+        let ocl_pq = &pro_ques[target];
+        let start_nonce = nonce[0];
+        let segment_start = Instant::now();
+
+        // construct the random segment of the message to hash (only the first
+        // `random_bytes` are used), leaving the rest of the salt empty
+        let salt = &salt[..random_bytes];
+
+        // Update the message buffer with new salt
+        message_buffer.write(salt).enq()?;
+
+        // Update the nonce buffer with initial nonce
+        nonce_buffer.write(&nonce[..]).enq()?;
+
+        // Clear solutions buffer before starting
+        solutions.fill(0);
+        solutions_buffer.write(&solutions[..]).enq()?;
+
+        {
+            let mut progress = search.progress[index].lock().unwrap();
+            progress.caller = *caller;
+            progress.salt = salt.to_vec();
+            progress.segments_used = segments_used;
+            progress.segment_progress = 0.0;
+        }
+
+        // repeatedly enqueue kernel to search for new addresses
+        loop {
+            // hold off between cycles while the search is paused, unless it
+            // is to stop
+            while paused.load(Ordering::Relaxed) && !stop.load(Ordering::Relaxed) {
+                std::thread::sleep(PAUSE_POLL_INTERVAL);
+            }
+
+            // build the kernel and define the type of each buffer
+            let kern = ocl_pq
+                .kernel_builder("hashMessage")
+                .arg_named("message", None::<&Buffer<u8>>)
+                .arg_named("nonce", None::<&Buffer<u64>>)
+                .arg_named("solutions", None::<&Buffer<u64>>)
+                .build()?;
+
+            // set each buffer
+            kern.set_arg("message", Some(&message_buffer))?;
+            kern.set_arg("nonce", Some(&nonce_buffer))?;
+            kern.set_arg("solutions", &solutions_buffer)?;
+
+            // enqueue the kernel with proper work group sizing
+            unsafe { 
+                kern.cmd()
+                    .global_work_size(global_work_size)
+                    .local_work_size(local_work_size)
+                    .enq()? 
+            };
+
+            // increment the cumulative nonce (does not reset after a match)
+            cumulative_nonce += 1;
+
+            // record the start time of the work
+            let work_start_time = Instant::now();
+
+            // sleep for most of the previous work duration (98% by default) to
+            // conserve CPU, rather than wait on the device the whole time
+            if work_duration_millis != 0 && config.poll_backoff > 0.0 {
+                std::thread::sleep(Duration::from_secs_f64(
+                    work_duration_millis as f64 / 1000.0 * config.poll_backoff,
+                ));
+            }
+
+            // read the solutions from the device
+            solutions_buffer.read(&mut solutions).enq()?;
+
+            // record the end time of the work and compute how long the work took
+            work_duration_millis = work_start_time.elapsed().as_millis() as u64;
+
+            {
+                let mut progress = search.progress[index].lock().unwrap();
+                progress.nonce = nonce[0];
+                progress.segment_progress = segment_progress(start_nonce, nonce[0], nonce_mask);
+                progress.cycles = cumulative_nonce;
+                // Account for 8x vectorization (each work item processes 8 nonces)
+                progress.work_size = global_work_size as u64 * VECTOR_WIDTH as u64;
+                progress.attempts += progress.work_size;
+                progress.work_duration_millis = work_duration_millis;
+            }
+
+            // if at least one solution is found, or the search has been asked
+            // to stop, has run out of time, or has ended on another device,
+            // end the loop; with several targets, also end it once this one
+            // has had its turn
+            if solutions.iter().any(|&x| x != 0)
+                || stop.load(Ordering::Relaxed)
+                || search.out_of_time()
+                || search.finished.load(Ordering::Relaxed)
+                || (pro_ques.len() > 1 && segment_start.elapsed() >= TARGET_TIME_SLICE)
+            {
+                break;
+            }
+
+            // if no solution has yet been found, increment the nonce, unless
+            // the nonce segment is used up: rather than wrap around and search
+            // it again, move on to a fresh random segment
+            match next_nonce(nonce[0], nonce_mask) {
+                Some(next) => nonce[0] = next,
+                None => break,
+            }
+
+            // update the nonce buffer with the incremented nonce value
+            nonce_buffer.write(&nonce[..]).enq()?;
+
+            // periodically save where the search is, to resume it if it's cut short
+            if checkpointing && last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL {
+                last_checkpoint = Instant::now();
+                save_checkpoint(
+                    config,
+                    Checkpoint {
+                        caller: *caller,
+                        init_code_hash: *init_code_hash,
+                        salt: salt.to_vec(),
+                        nonce: nonce[0],
+                        cumulative_nonce,
+                        segments_used,
+                    },
+                );
+            }
+        }
+
+        // carry on from the segment just searched, which after resuming is
+        // the one from the checkpoint
+        seeded_segment[..random_bytes].copy_from_slice(salt);
+        (seeded_nonce, _) = advance(&mut seeded_segment[..random_bytes], nonce[0], nonce_mask);
+
+        // every work item has searched each nonce from the start up to the current one
+        if let Some(trace) = &search.trace {
+            trace.record(caller, salt, start_nonce..=nonce[0]);
+        }
+
+        // iterate over each solution, first converting to a fixed array
+        let preimage = config.preimage(init_code_hash);
+        for &solution in &solutions {
+            if solution == 0 {
+                continue;
+            }
+
+            // the low four bytes of each solution hold the work item segment
+            let solution = solution.to_le_bytes();
+            let work_item_segment = &solution[..4];
+            let nonce_segment = &nonce[0].to_le_bytes()[..nonce_bytes];
+
+            let work_item_start = caller_bytes + random_bytes;
+            let nonce_start = work_item_start + WORK_ITEM_BYTES;
+
+            let mut solution_salt = [0; 32];
+            solution_salt[..caller_bytes].copy_from_slice(&caller[..caller_bytes]);
+            solution_salt[caller_bytes..work_item_start].copy_from_slice(salt);
+            solution_salt[work_item_start..nonce_start].copy_from_slice(work_item_segment);
+            solution_salt[nonce_start..].copy_from_slice(nonce_segment);
+
+            // get the address that results from the salt
+            let solution_salt = &solution_salt;
+            let address = &preimage.address(solution_salt);
+
+            // count total, leading, and consecutive zero bytes
+            let score = score_address(address);
+            let Score { leading, total, .. } = score;
+
+            // a solution that doesn't actually meet the criteria the kernel
+            // checked points to a faulty (e.g. overclocked) device, not a find;
+            // it is shown on the dashboard apart from the solutions found, and
+            // never written to the results
+            if !meets_kernel_criteria(config, address, &score) {
+                search.rejected.fetch_add(1, Ordering::Relaxed);
+                let mismatch = format!(
+                    "kernel mismatch, discarded: 0x{} => 0x{} ({leading} / {total})",
+                    hex::encode(solution_salt),
+                    hex::encode(address),
+                );
+                if search.log_lines {
+                    term.write_line(&mismatch)?;
+                }
+                *search.last_mismatch.lock().unwrap() = Some(mismatch);
+                continue;
+            }
+
+            // the kernel thresholds let through some candidates that don't
+            // actually beat the reference address, so check them exactly
+            if let Some(beat_score) = search.beat_score {
+                if !score.beats(&beat_score) {
+                    continue;
+                }
+            }
+
+            // skip an address containing any denied bytes, which is left to
+            // this check on the rare solutions rather than done by the kernel
+            if contains_any(address, &config.deny) {
+                continue;
+            }
+
+            // look up the reward amount, skipping the address if it isn't
+            // high enough when asked for a minimum
+            let reward_key = score.reward_key(config.zero_granularity);
+            let reward = search.rewards.get(&reward_key).unwrap_or("0");
+            if let Some(min_reward) = config.min_reward {
+                if !reward_at_least(reward, min_reward) {
+                    continue;
+                }
+            }
+
+            // a salt can be reported again, e.g. by another device or after
+            // resuming, but it is only recorded once
+            if !search.seen.insert(solution_salt) {
+                continue;
+            }
+
+            // the kernel only knows the initial bound, so skip anything that is
+            // no smaller than the smallest address found since
+            if let Some(bound) = search.smallest.lock().unwrap().as_mut() {
+                if !is_below(address, bound) {
+                    continue;
+                }
+                *bound = (*address).into();
+            }
+
+            let full_salt = format!("0x{}", hex::encode(solution_salt));
+            let shown = display_address(address, config.checksum_addresses);
+            let description = search.rewards.describe(score.leading, score.total);
+            let mut output = format!("{full_salt} => {shown} => {reward} => {description}");
+
+            // with several callers, say which one the salt is for
+            if config.callers.len() > 1 {
+                write!(output, " => caller 0x{}", hex::encode(caller)).unwrap();
+            }
+
+            // with several init code hashes, say which one the address is for
+            if config.init_code_hashes.len() > 1 {
+                let init_code_hash = hex::encode(init_code_hash);
+                write!(output, " => init code hash 0x{init_code_hash}").unwrap();
+            }
+
+            // in prefix mode, note how many nibbles were matched
+            if let Some(prefix) = &config.prefix {
+                write!(output, " => prefix {}", prefix.len()).unwrap();
+            }
+
+            // in pattern mode, note which bytes were matched where
+            if let Some((offset, pattern)) = &config.pattern {
+                write!(output, " => pattern 0x{} at {offset}", hex::encode(pattern)).unwrap();
+            }
+
+            // in repeat mode, note how long the longest repeat is
+            if config.min_repeat.is_some() {
+                write!(output, " => repeat {}", repeat_score(address).longest()).unwrap();
+            }
+
+            // in mask mode, note how many bytes were matched
+            if let Some(mask) = &config.mask {
+                write!(output, " => mask {}", mask.len()).unwrap();
+            }
+
+            // optionally note the gas the address is estimated to save
+            if config.show_gas {
+                write!(output, " => {}", gas_savings(leading, total)).unwrap();
+            }
+
+            // optionally append the full preimage that was hashed
+            if config.show_preimage {
+                let solution_message = preimage.message(solution_salt);
+                write!(output, " => 0x{}", hex::encode(solution_message)).unwrap();
+            }
+
+            // optionally replace the line with a JSON record, a self-contained
+            // proof bundle, or a Solidity declaration
+            match config.output_format {
+                OutputFormat::Text => {}
+                OutputFormat::Json => {
+                    let record = FoundRecord {
+                        address: shown.clone(),
+                        init_code_hash: (config.init_code_hashes.len() > 1)
+                            .then(|| format!("0x{}", hex::encode(init_code_hash))),
+                        ..FoundRecord::new(solution_salt, address, &score, reward)
+                    };
+                    output = record.to_json();
+                }
+                OutputFormat::Bundle => {
+                    let mut bundle = ProofBundle {
+                        address: shown.clone(),
+                        ..ProofBundle::new(
+                            &config.factory_address,
+                            solution_salt,
+                            init_code_hash,
+                            address,
+                        )
+                    };
+                    // without a caller segment, the salt isn't for any caller
+                    if !config.use_caller_salt {
+                        bundle.caller = None;
+                    }
+                    output = bundle.to_json();
+                }
+                OutputFormat::Solidity => {
+                    let init_code_hash =
+                        (config.init_code_hashes.len() > 1).then_some(init_code_hash);
+                    output = solidity_declaration(solution_salt, address, init_code_hash);
+                }
+            }
+
+            let mut show = format!("{output} ({leading} / {total})");
+
+            // optionally show how the address looks when truncated by wallets
+            if config.show_preview {
+                write!(show, " [{}]", truncated_display(address)).unwrap();
+            }
+
+            // with --best, only keep the solution if it is the best so far,
+            // listing each new best one on the dashboard
+            if config.best {
+                let candidate = BestFound {
+                    reward: reward.parse().unwrap_or(0.0),
+                    leading,
+                    output,
+                    show,
+                };
+                let mut best = search.best.lock().unwrap();
+                if best.as_ref().map_or(true, |best| candidate.beats(best)) {
+                    let mut found_list = search.found_list.lock().unwrap();
+                    found_list.push(candidate.show.clone());
+                    *best = Some(candidate);
+                }
+                search.found.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+
+            if search.log_lines {
+                term.write_line(&show)?;
+            }
+            search.found_list.lock().unwrap().push(show.to_string());
+
+            // the first interesting address ends the quiet period
+            if config
+                .quiet_until
+                .map_or(false, |until| leading >= until as usize)
+                && search.quiet.swap(false, Ordering::Relaxed)
+            {
+                term.write_line("")?;
+                term.write_line(&format!(
+                    "*** found an address with {leading} leading zero bytes ***"
+                ))?;
+                term.write_line(&show)?;
+            }
+
+            #[cfg(feature = "clipboard")]
+            if config.copy_to_clipboard && !search.copied.swap(true, Ordering::Relaxed) {
+                clipboard::copy_to_clipboard(&format!("{full_salt}\n{shown}"));
+            }
+
+            #[cfg(feature = "hooks")]
+            if let Some(command) = &config.on_found_command {
+                hooks::run_on_found(command, &full_salt, &shown, leading, total, config.no_file);
+            }
+
+            search.results.write(leading, &output);
+            if let Some(highlights) = &search.highlights {
+                highlights.record(&score, &output);
+            }
+            search.found.fetch_add(1, Ordering::Relaxed);
+
+            // a single address that beats the reference is all we need
+            if search.beat_score.is_some() {
+                return Ok(());
+            }
+        }
+
+        // stop once the requested number of addresses has been found, with
+        // every solution of the final cycle written
+        if config
+            .max_results
+            .map_or(false, |max| search.found.load(Ordering::Relaxed) >= max)
+        {
+            return Ok(());
+        }
+
+        // when asked to stop or out of time, save where the search is to
+        // resume it later
+        if stop.load(Ordering::Relaxed) || search.out_of_time() {
+            if checkpointing {
+                let checkpoint = checkpoint_after(
+                    config,
+                    target,
+                    salt,
+                    nonce[0],
+                    cumulative_nonce,
+                    segments_used,
+                );
+                save_checkpoint(config, checkpoint);
+            }
+            return Ok(());
+        }
+
+        // the search has ended on another device
+        if search.finished.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+    }
+}
+
+/// The rewards to score addresses with: the configured table, if any, or else
+/// the built-in one.
+fn load_rewards(config: &Config) -> Result<Reward, String> {
+    match &config.reward_table {
+        Some(path) => Reward::from_file(path),
+        None => Ok(Reward::new()),
+    }
+}
+
+/// Print which shapes of address with a reward (of at least `--min-reward`, if
+/// given) the search captures, so that it's clear up front what it is mining
+/// for: those meeting the zero byte thresholds with OpenCL, or those with at
+/// least three zero bytes on the CPU, which doesn't go by the thresholds.
+/// Other criteria, or leading zero nibbles, don't go by these shapes, so
+/// nothing is printed for them.
+fn print_captured_shapes(config: &Config, rewards: &Reward) {
+    if config.zero_granularity != Granularity::Byte
+        || config.beat_address.is_some()
+        || config.min_zero_run.is_some()
+        || config.max_address.is_some()
+        || config.prefix.is_some()
+        || config.pattern.is_some()
+        || config.min_repeat.is_some()
+        || config.mask.is_some()
+    {
+        return;
+    }
+
+    let leading_threshold = config.leading_zeroes_threshold as usize;
+    let total_threshold = config.total_zeroes_threshold as usize;
+    let min_reward = config.min_reward.map_or(0.0, f64::from);
+    let is_cpu = config.is_cpu();
+    let captured: Vec<_> = rewards
+        .combos_at_least(min_reward)
+        .into_iter()
+        .filter(|&(leading, total)| match is_cpu {
+            true => total >= 3 || (config.once && total >= total_threshold),
+            false => leading >= leading_threshold || total >= total_threshold,
+        })
+        .collect();
+    let criteria = if is_cpu { "search" } else { "these thresholds" };
+    if captured.is_empty() {
+        status!(
+            config,
+            "No address with a reward meets the criteria of this {criteria}."
+        );
+        return;
+    }
+    let shapes = describe_shapes(&captured, &rewards.combos_at_least(0.0));
+    status!(
+        config,
+        "Addresses with a reward {criteria} capture: {shapes}"
+    );
+}
+
+/// Describe shapes of address given as `(leading, total)` zero bytes, e.g.
+/// `0 leading and 5-19 total, or 3+ leading zero bytes`: the totals for each
+/// number of leading zero bytes in turn, down to the number from which every
+/// shape with a reward (one of `rewarded`) is included.
+fn describe_shapes(shapes: &[(usize, usize)], rewarded: &[(usize, usize)]) -> String {
+    let totals = |combos: &[(usize, usize)], leading: usize| -> Vec<usize> {
+        combos
+            .iter()
+            .filter(|combo| combo.0 == leading)
+            .map(|combo| combo.1)
+            .collect()
+    };
+    let all_from = (0..=21)
+        .rev()
+        .take_while(|&leading| totals(shapes, leading) == totals(rewarded, leading))
+        .last()
+        .unwrap_or(21);
+
+    let mut parts = vec![];
+    for leading in 0..all_from {
+        // group the totals into runs, e.g. `5-7/9-19`
+        let mut runs: Vec<(usize, usize)> = vec![];
+        for total in totals(shapes, leading) {
+            match runs.last_mut() {
+                Some(run) if run.1 + 1 == total => run.1 = total,
+                _ => runs.push((total, total)),
+            }
+        }
+        if runs.is_empty() {
+            continue;
+        }
+        let runs: Vec<String> = runs
+            .into_iter()
+            .map(|(first, last)| {
+                if first == last {
+                    format!("{first}")
+                } else {
+                    format!("{first}-{last}")
+                }
+            })
+            .collect();
+        parts.push(format!("{leading} leading and {} total", runs.join("/")));
+    }
+    if all_from == 0 {
+        parts.push("all of them".to_string());
+    } else if all_from <= 20 && shapes.iter().any(|&(leading, _)| leading >= all_from) {
+        parts.push(format!("{all_from}+ leading zero bytes"));
+    }
+
+    match parts.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{}, or {last}", rest.join(", ")),
+        _ => parts.join(""),
+    }
+}
+
+/// The order in which the CPU search goes through the segments of `targets`
+/// targets: a chunk of the nonces of each target in turn, then the next chunk
+/// of each, until every nonce of every segment has been searched.
+fn target_chunks(targets: usize) -> impl Iterator<Item = (usize, RangeInclusive<u64>)> {
+    let chunks = (MAX_INCREMENTER + 1) / TARGET_CHUNK;
+    (0..chunks).flat_map(move |chunk| {
+        let nonces = chunk * TARGET_CHUNK..=(chunk + 1) * TARGET_CHUNK - 1;
+        (0..targets).map(move |target| (target, nonces.clone()))
+    })
+}
+
+/// The nonce to search after `nonce`, or `None` once the nonce segment of the
+/// salt (`nonce_mask`) is used up and the next one would wrap around.
+fn next_nonce(nonce: u64, nonce_mask: u64) -> Option<u64> {
+    (nonce < nonce_mask).then(|| nonce + 1)
+}
+
+/// Move on from `nonce` of the random segment `salt`: to the next nonce, or
+/// once the nonce segment is used up, to the start of the random segment
+/// counted up from it. Returns the nonce, and whether the segment changed.
+fn advance(salt: &mut [u8], nonce: u64, nonce_mask: u64) -> (u64, bool) {
+    match next_nonce(nonce, nonce_mask) {
+        Some(next) => (next, false),
+        None => {
+            count_up(salt);
+            (0, true)
+        }
+    }
+}
+
+/// The checkpoint to resume a GPU search from once every nonce of the random
+/// segment `salt` up to `nonce` has been searched with `target`: the next
+/// nonce of the segment, or once it is used up, the start of a fresh segment
+/// with the next target in turn.
+fn checkpoint_after(
+    config: &Config,
+    target: usize,
+    salt: &[u8],
+    nonce: u64,
+    cumulative_nonce: u64,
+    segments_used: u64,
+) -> Checkpoint {
+    let nonce_mask = u64::MAX >> (64 - 8 * config.salt_nonce_bytes as usize);
+    let mut salt = salt.to_vec();
+    let (nonce, fresh) = advance(&mut salt, nonce, nonce_mask);
+    let (target, segments_used) = match fresh {
+        true => {
+            let targets = config.callers.len() * config.init_code_hashes.len();
+            ((segments_used % targets as u64) as usize, segments_used + 1)
+        }
+        false => (target, segments_used),
+    };
+    Checkpoint {
+        caller: config.callers[target % config.callers.len()],
+        init_code_hash: config.init_code_hashes[target / config.callers.len()],
+        salt,
+        nonce,
+        cumulative_nonce,
+        segments_used,
+    }
+}
+
+/// The share of a nonce segment that has been searched once every nonce from
+/// `start` up to `nonce` has, out of those from `start` up to `nonce_mask`;
+/// after resuming, the segment is taken to start where the search resumed.
+fn segment_progress(start: u64, nonce: u64, nonce_mask: u64) -> f64 {
+    (nonce - start + 1) as f64 / ((nonce_mask - start) as f64 + 1.0)
+}
+
+/// A share as a percentage: rounded down to a whole one, so that a segment
+/// isn't shown as done before it is, or in scientific notation below 1%.
+fn percent(share: f64) -> String {
+    let percent = share * 100.0;
+    if percent == 0.0 || percent >= 1.0 {
+        format!("{}%", percent.floor())
+    } else {
+        format!("{percent:.1e}%")
+    }
+}
+
+/// Count a big-endian number up by one, wrapping around to zero.
+fn count_up(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut().rev() {
+        *byte = byte.wrapping_add(1);
+        if *byte != 0 {
+            break;
+        }
+    }
+}
+
+/// A line with the runtime, rate (in attempts per second), number found and
+/// share of the current nonce segment searched so far of a search.
+fn progress_line(runtime: Duration, rate: f64, found: u64, segment: f64) -> String {
+    let secs = runtime.as_secs();
+    format!(
+        "{}:{:02}:{:02} - {:.2} million attempts per second - {} found - \
+         segment progress: {}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        rate / 1_000_000.0,
+        found,
+        percent(segment),
+    )
+}
+
+/// The header of `#` lines that starts the output of a run with `--annotate`,
+/// recording what the run searched for and when it started.
+fn run_header(config: &Config) -> String {
+    let callers: Vec<String> = if config.use_caller_salt {
+        config
+            .callers
+            .iter()
+            .map(|c| format!("0x{}", hex::encode(c)))
+            .collect()
+    } else {
+        vec!["none (--no-caller-salt)".to_string()]
+    };
+    let hashes: Vec<String> = config
+        .init_code_hashes
+        .iter()
+        .map(|h| format!("0x{}", hex::encode(h)))
+        .collect();
+    format!(
+        "# run started {}\n\
+         # factory: 0x{}\n\
+         # caller: {}\n\
+         # init code hash: {}\n\
+         # thresholds: {} leading / {} total zeroes",
+        utc_timestamp(SystemTime::now()),
+        hex::encode(config.factory_address),
+        callers.join(", "),
+        hashes.join(", "),
+        config.leading_zeroes_threshold,
+        config.total_zeroes_threshold,
+    )
+}
+
+/// Save the state of a GPU search, warning rather than failing if it can't be.
+fn save_checkpoint(config: &Config, checkpoint: Checkpoint) {
+    if let Err(e) = checkpoint.save(Path::new(CHECKPOINT_PATH)) {
+        status!(
+            config,
+            "Warning: couldn't save the search state to `{CHECKPOINT_PATH}`: {e}"
+        );
+    }
+}
+
+/// The best solution of a `--best` search so far, held in memory until the
+/// time is up: its reward, leading zero bytes, and the lines to write and show
+/// for it.
+struct BestFound {
+    reward: f64,
+    leading: usize,
+    output: String,
+    show: String,
+}
+
+impl BestFound {
+    /// Whether this solution has a higher reward than `other`, or the same
+    /// reward and more leading zero bytes.
+    fn beats(&self, other: &Self) -> bool {
+        (self.reward, self.leading) > (other.reward, other.leading)
+    }
+}
+
+/// Print and write the winner of a `--best` search, if anything was found.
+fn write_best(config: &Config, results: &BufferedResults, best: Option<BestFound>) {
+    if let Some(best) = best {
+        status!(config, "Best address found: {}", best.show);
+        results.write(best.leading, &best.output);
+    }
+}
+
+/// Report how long a search that was asked to stop ran and what it found.
+fn print_summary(config: &Config, found: u64, elapsed: Duration) {
+    let secs = elapsed.as_secs();
+    status!(
+        config,
+        "Stopped after {}:{:02}:{:02}, {found} found this run.",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    );
+}
+
+/// Where the search writes its results: the results file, or the tier files,
+/// each starting with the run header when annotating, or stdout with
+/// `--no-file`; in batches with `--flush-every`.
+fn result_writer(config: &Config) -> Result<BufferedResults, Create2Error> {
+    let header = config.annotate.then(|| run_header(config));
+    let writer = if config.no_file {
+        ResultWriter::stdout(header)
+    } else {
+        let path = &config.output_path;
+        ResultWriter::new(path, config.tiered_output, config.max_file_size, header)?
+    };
+    Ok(BufferedResults::new(writer, config.flush_every))
+}
+
+/// The terminal that progress and the dashboard are shown on, which is stderr
+/// with `--no-file` so that they stay out of the results.
+fn status_term(config: &Config) -> Term {
+    if config.no_file {
+        Term::stderr()
+    } else {
+        Term::stdout()
+    }
+}
 
-                // get the full salt used to create the address
-                let header_hex_string = hex::encode(header);
-                let body_hex_string = hex::encode(salt_incremented_segment);
-                let full_salt = format!("0x{}{}", &header_hex_string[42..], &body_hex_string);
+/// Whether `term` is a terminal that the dashboard and the CPU status line can
+/// be redrawn on in place, which takes ANSI escape codes. On Windows, `console`
+/// turns them on where the console supports them, and says whether it could;
+/// elsewhere, any terminal but a dumb one takes them.
+fn redraws_in_place(term: &Term) -> bool {
+    if !term.is_term() {
+        false
+    } else if cfg!(windows) {
+        term.features().colors_supported()
+    } else {
+        std::env::var("TERM").map_or(true, |name| name != "dumb")
+    }
+}
 
-                // display the salt and the address.
-                let output = format!(
-                    "{full_salt} => {address} => {}",
-                    reward_amount.unwrap_or("0")
-                );
-                println!("{output}");
+/// Measure the raw hash rate (in hashes per second) of the OpenCL kernel on the
+/// configured device by running it back to back for `duration`, with
+/// thresholds from `config` that should keep it from reporting solutions.
+/// Solutions aren't read back, so this measures the kernel alone.
+pub fn gpu_hash_rate(config: &Config, duration: Duration) -> ocl::Result<f64> {
+    let (hashes, elapsed) = run_kernel(config, &[], 0, false, |_, elapsed, _| elapsed < duration)?;
+    Ok(hashes as f64 / elapsed.as_secs_f64())
+}
 
-                // create a lock on the file before writing
-                file.lock_exclusive().expect("Couldn't lock file.");
+/// The hash rate achieved by [`bench`].
+#[derive(Clone, Copy, Debug)]
+pub struct BenchResult {
+    pub total_hashes: u64,
+    pub elapsed: Duration,
+    pub hashes_per_sec: f64,
+}
 
-                // write the result to file
-                writeln!(&file, "{output}")
-                    .expect("Couldn't write to `efficient_addresses.txt` file.");
+/// Run the OpenCL search on the configured device for a fixed number of kernel
+/// cycles (`batches`), reading back the solutions after each one as the search
+/// does but without recording them, and report the hash rate achieved. This
+/// gives a repeatable way to compare settings such as `--work-size`.
+pub fn bench(config: Config, batches: u32) -> Result<BenchResult, Create2Error> {
+    let keep_going = |cycles, _, _: &[u64]| cycles < batches as u64;
+    let (total_hashes, elapsed) = run_kernel(&config, &[], 0, true, keep_going)?;
+    Ok(BenchResult {
+        total_hashes,
+        elapsed,
+        hashes_per_sec: total_hashes as f64 / elapsed.as_secs_f64(),
+    })
+}
 
-                // release the file lock
-                FileExt::unlock(&file).expect("Couldn't unlock file.");
-            });
+/// Check that the search could start, without starting it: that each
+/// configured OpenCL device can be found, and that the kernel builds for it
+/// with every caller and init code hash. The CPU search needs nothing beyond
+/// valid arguments, which a `Config` already has.
+pub fn check(config: &Config) -> Result<(), Create2Error> {
+    if config.is_cpu() {
+        return Ok(());
     }
+    for selector in &config.gpu_devices {
+        let (platform, device) = select_device(config, selector)?;
+        let context = Context::builder()
+            .platform(platform)
+            .devices(device)
+            .build()?;
+        for init_code_hash in &config.init_code_hashes {
+            for caller in &config.callers {
+                let src = mk_kernel_src(config, caller, init_code_hash);
+                build_kernel(&context, device, src)?;
+            }
+        }
+    }
+    Ok(())
 }
 
-/// Given a Config object with a factory address, a caller address, a keccak-256
-/// hash of the contract initialization code, and a device ID, search for salts
-/// using OpenCL that will enable the factory contract to deploy a contract to a
-/// gas-efficient address via CREATE2. This method also takes threshold values
-/// for both leading zero bytes and total zero bytes - any address that does not
-/// meet or exceed the threshold will not be returned. Default threshold values
-/// are three leading zeroes or five total zeroes.
-///
-/// The 32-byte salt is constructed as follows:
-///   - the 20-byte calling address (to prevent frontrunning)
-///   - a random 4-byte segment (to prevent collisions with other runs)
-///   - a 4-byte segment unique to each work group running in parallel
-///   - a 4-byte nonce segment (incrementally stepped through during the run)
-///
-/// When a salt that will result in the creation of a gas-efficient contract
-/// address is found, it will be appended to `efficient_addresses.txt` along
-/// with the resultant address and the "value" (i.e. approximate rarity) of the
-/// resultant address.
-///
-/// This method is still highly experimental and could almost certainly use
-/// further optimization - contributions are more than welcome!
-pub fn gpu(config: Config) -> ocl::Result<()> {
-    println!(
-        "Setting up experimental OpenCL miner using device {}...",
-        config.gpu_device
-    );
+/// Run the kernel on the first configured device with the random segment
+/// `segment`, from the nonce `first_nonce` up, for as long as `keep_going`
+/// (given the cycles run, the time taken so far, and the solutions of the last
+/// cycle) says to, optionally reading back the solutions after each cycle, and
+/// return the number of hashes computed and the time taken.
+fn run_kernel(
+    config: &Config,
+    segment: &[u8],
+    first_nonce: u64,
+    read_solutions: bool,
+    mut keep_going: impl FnMut(u64, Duration, &[u64]) -> bool,
+) -> ocl::Result<(u64, Duration)> {
+    let (platform, device) = select_device(config, &config.gpu_devices[0])?;
+    let (local_work_size, global_work_size) = work_sizes(config, device);
 
-    // (create if necessary) and open a file where found salts will be written
-    let file = output_file();
+    let context = Context::builder()
+        .platform(platform)
+        .devices(device)
+        .build()?;
+    let queue = Queue::new(&context, device, None)?;
+    let src = mk_kernel_src(config, &config.callers[0], &config.init_code_hashes[0]);
+    let program = build_kernel(&context, device, src)?;
+    let ocl_pq = ProQue::new(context, queue.clone(), program, Some(global_work_size));
 
-    // create object for computing rewards (relative rarity) for a given address
-    let rewards = Reward::new();
+    let mut message = [0; MAX_RANDOM_BYTES];
+    message[..segment.len()].copy_from_slice(segment);
+    let message_buffer = Buffer::builder()
+        .queue(queue.clone())
+        .flags(MemFlags::new().read_write())
+        .len(MAX_RANDOM_BYTES)
+        .copy_host_slice(&message)
+        .build()?;
+    let nonce_buffer = Buffer::builder()
+        .queue(queue.clone())
+        .flags(MemFlags::new().read_write())
+        .len(1)
+        .copy_host_slice(&[0u64])
+        .build()?;
+    let solutions_buffer = Buffer::builder()
+        .queue(queue.clone())
+        .flags(MemFlags::new().write_only())
+        .len(SOLUTION_SLOTS)
+        .copy_host_slice(&[0u64; SOLUTION_SLOTS as usize])
+        .build()?;
 
-    // track how many addresses have been found and information about them
-    let mut found: u64 = 0;
-    let mut found_list: Vec<String> = vec![];
+    let kern = ocl_pq
+        .kernel_builder("hashMessage")
+        .arg_named("message", None::<&Buffer<u8>>)
+        .arg_named("nonce", None::<&Buffer<u64>>)
+        .arg_named("solutions", None::<&Buffer<u64>>)
+        .build()?;
+    kern.set_arg("message", Some(&message_buffer))?;
+    kern.set_arg("nonce", Some(&nonce_buffer))?;
+    kern.set_arg("solutions", &solutions_buffer)?;
 
-    // set up a controller for terminal output
-    let term = Term::stdout();
+    let mut solutions = vec![0u64; SOLUTION_SLOTS as usize];
+    let start = Instant::now();
+    let mut cycles: u64 = 0;
+    while keep_going(cycles, start.elapsed(), &solutions) {
+        nonce_buffer.write(&[first_nonce + cycles][..]).enq()?;
+        unsafe {
+            kern.cmd()
+                .global_work_size(global_work_size)
+                .local_work_size(local_work_size)
+                .enq()?
+        };
+        if read_solutions {
+            solutions_buffer.read(&mut solutions).enq()?;
+        }
+        queue.finish()?;
+        cycles += 1;
+    }
+
+    let hashes = cycles * global_work_size as u64 * VECTOR_WIDTH as u64;
+    Ok((hashes, start.elapsed()))
+}
 
-    // Find NVIDIA platform instead of using default
+/// Select the OpenCL platform and the device given by `selector`, listing the
+/// available ones and warning when the device may run out of memory.
+fn select_device(config: &Config, selector: &DeviceSelector) -> ocl::Result<(Platform, Device)> {
     let platforms = Platform::list();
-    println!("Available OpenCL platforms:");
+    status!(config, "Available OpenCL platforms:");
     for (i, platform) in platforms.iter().enumerate() {
-        println!("  Platform {}: {}", i, platform.name().unwrap_or_else(|_| "Unknown".to_string()));
+        status!(
+            config,
+            "  Platform {}: {}",
+            i,
+            platform.name().unwrap_or_else(|_| "Unknown".to_string())
+        );
     }
-    
-    // Try to find NVIDIA platform, fall back to default if not found
-    let platform = platforms.iter()
-        .find(|p| p.name().unwrap_or_default().contains("NVIDIA"))
-        .cloned()
-        .unwrap_or_else(|| Platform::new(ocl::core::default_platform().unwrap()));
-    
-    println!("Selected OpenCL Platform: {}", platform.name().unwrap_or_else(|_| "Unknown".to_string()));
 
-    // List available devices on this platform
-    let devices = Device::list_all(platform)?;
-    println!("Available devices on selected platform:");
+    // use the platform given with --platform, or else look for a platform with
+    // a device of the requested type, preferring one with a GPU, and fall back
+    // to the default if none has one
+    let has_devices = |p: &Platform, device_type: DeviceType| {
+        Device::list(*p, Some(device_type)).map_or(false, |d| !d.is_empty())
+    };
+    let usable: Vec<_> = platforms
+        .iter()
+        .filter(|p| has_devices(p, config.device_type))
+        .collect();
+    let platform = match &config.platform {
+        Some(selector) => selector.resolve(&platforms)?,
+        None => usable
+            .iter()
+            .find(|p| has_devices(p, DeviceType::GPU))
+            .or_else(|| usable.first())
+            .copied()
+            .cloned()
+            .unwrap_or_else(|| Platform::new(ocl::core::default_platform().unwrap())),
+    };
+
+    status!(
+        config,
+        "Selected OpenCL Platform: {}",
+        platform.name().unwrap_or_else(|_| "Unknown".to_string())
+    );
+
+    // List available devices of the requested type on this platform
+    let devices = Device::list(platform, Some(config.device_type))?;
+    status!(config, "Available devices on selected platform:");
     for (i, device) in devices.iter().enumerate() {
-        println!("  Device {}: {}", i, device.name().unwrap_or_else(|_| "Unknown".to_string()));
+        status!(
+            config,
+            "  Device {}: {}",
+            i,
+            device.name().unwrap_or_else(|_| "Unknown".to_string())
+        );
     }
     
-    // set up the device to use
-    let device = Device::by_idx_wrap(platform, config.gpu_device as usize)?;
-    println!("Selected OpenCL Device: {}", device.name().unwrap_or_else(|_| "Unknown".to_string()));
+    // set up the device to use, falling back to the first one when asked to
+    // rather than failing on an index past the last device
+    let device = match selector.resolve(platform, config.device_type) {
+        Err(e) if config.auto_device && matches!(selector, DeviceSelector::Index(_)) => {
+            let Some(&device) = devices.first() else {
+                return Err(e);
+            };
+            status!(
+                config,
+                "Warning: {e}. Falling back to device 0 (--auto-device)."
+            );
+            device
+        }
+        result => result?,
+    };
+    status!(
+        config,
+        "Selected OpenCL Device: {}",
+        device.name().unwrap_or_else(|_| "Unknown".to_string())
+    );
+    // warn before allocating if the search would come close to exhausting the
+    // device; without its number of compute units, every work item of a cycle
+    // is taken to be resident at once
+    let compute_units = match device.info(OclDeviceInfo::MaxComputeUnits) {
+        Ok(DeviceInfoResult::MaxComputeUnits(units)) => units,
+        _ => u32::MAX,
+    };
+    let max_wg_size = device.max_wg_size().unwrap_or(256);
+    let needed_memory = estimated_device_memory(
+        config.work_size,
+        VECTOR_WIDTH,
+        SOLUTION_SLOTS,
+        compute_units,
+        max_wg_size,
+    );
+    status!(
+        config,
+        "Estimated Device Memory: {} bytes",
+        needed_memory.separated_string()
+    );
+    let global_memory = device.info(OclDeviceInfo::GlobalMemSize);
+    if let Ok(DeviceInfoResult::GlobalMemSize(available)) = global_memory {
+        if needed_memory > available / 10 * 9 {
+            status!(
+                config,
+                "Warning: this search may need more than 90% of the device's {} bytes of \
+                 global memory - consider lowering --work-size.",
+                available.separated_string()
+            );
+        }
+    }
+
+    Ok((platform, device))
+}
+
+/// Determine the local and global work sizes to run the kernel with.
+fn work_sizes(config: &Config, device: Device) -> (u32, u32) {
     let max_wg_size = device.max_wg_size().unwrap_or(256);
-    println!("Max Work Group Size: {}", max_wg_size);
+    status!(config, "Max Work Group Size: {}", max_wg_size);
     
     // Calculate optimal local work size (typically 256 or 512 for modern GPUs)
     let local_work_size = std::cmp::min(max_wg_size as u32, 512);
-    println!("Using Local Work Size: {}", local_work_size);
+    status!(config, "Using Local Work Size: {}", local_work_size);
     
     // Ensure global work size is multiple of local work size
     // Divide by 8 for vectorization (each work item processes 8 nonces)
-    let vectorized_work_size = WORK_SIZE / 8;
+    let vectorized_work_size = config.work_size / VECTOR_WIDTH;
     let global_work_size = ((vectorized_work_size + local_work_size - 1) / local_work_size) * local_work_size;
-    println!("Using Global Work Size: {} (8x vectorized from {})", global_work_size, WORK_SIZE);
+    status!(
+        config,
+        "Using Global Work Size: {} (8x vectorized from {})",
+        global_work_size, config.work_size
+    );
 
-    // set up the context to use
-    let context = Context::builder()
-        .platform(platform)
-        .devices(device)
-        .build()?;
+    (local_work_size, global_work_size)
+}
 
-    // set up the program to use
-    let program = Program::builder()
-        .devices(device)
-        .src(mk_kernel_src(&config))
-        .build(&context)?;
+/// The probability that the kernel built by `mk_kernel_src` reports a random
+/// address, if it can be told up front: it can't when beating an address,
+/// looking for a run of zero or repeated bytes, or looking for ever smaller
+/// addresses.
+fn match_probability(config: &Config) -> Option<f64> {
+    if config.beat_address.is_some()
+        || config.min_zero_run.is_some()
+        || config.max_address.is_some()
+        || config.min_repeat.is_some()
+    {
+        return None;
+    }
 
-    // set up the queue to use
-    let queue = Queue::new(&context, device, None)?;
+    let criteria = match (&config.prefix, &config.pattern) {
+        (Some(prefix), _) => 16f64.powi(-(prefix.len() as i32)),
+        (None, Some((_, pattern))) => 256f64.powi(-(pattern.len() as i32)),
+        (None, None) if config.mask.is_some() => {
+            let mask = config.mask.as_deref().unwrap_or_default();
+            256f64.powi(-(mask.len() as i32))
+        }
+        (None, None) => threshold_probability(
+            config.leading_zeroes_threshold,
+            config.total_zeroes_threshold,
+            config.zero_granularity,
+        ),
+    };
 
-    // set up the "proqueue" (or amalgamation of various elements) to use
-    let ocl_pq = ProQue::new(context, queue, program, Some(global_work_size));
+    // the tag and suffix are checked on top of the other criteria
+    let tag_bytes = config.tag.map_or(0, |tag| tag.len());
+    let suffix_bytes = config.suffix.as_ref().map_or(0, Vec::len);
+    Some(criteria * 256f64.powi(-((tag_bytes + suffix_bytes) as i32)))
+}
 
-    // create a random number generator
-    let mut rng = thread_rng();
+/// An address that meets the criteria of the CPU search, as judged by
+/// [`evaluate_address`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Match<'a> {
+    /// The leading, total, and consecutive zero bytes of the address.
+    score: Score,
+    /// The reward (approximate rarity) of the address, if it has one.
+    reward: Option<&'a str>,
+}
 
-    // determine the start time
-    let start_time: f64 = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs_f64();
+/// Check an address against the criteria of the CPU search, scoring it and
+/// looking up its reward if it meets them. With `--beat`, `beat_score` is the
+/// score of the reference address, computed once for the whole search. With
+/// `--max-address`, the address only has to be below the bound here; the
+/// search then also checks that it is smaller than any found so far.
+fn evaluate_address<'a>(
+    address: &Address,
+    rewards: &'a Reward,
+    config: &Config,
+    beat_score: Option<&Score>,
+) -> Option<Match<'a>> {
+    // only proceed if the address ends with the requested tag
+    if let Some(tag) = config.tag {
+        if address[16..] != tag {
+            return None;
+        }
+    }
 
-    // set up variables for tracking performance
-    let mut rate: f64 = 0.0;
-    let mut cumulative_nonce: u64 = 0;
+    // only proceed if the address ends with the requested suffix
+    if let Some(suffix) = &config.suffix {
+        if !has_suffix(address, suffix) {
+            return None;
+        }
+    }
 
-    // the previous timestamp of printing to the terminal
-    let mut previous_time: f64 = 0.0;
+    // only proceed if the checksummed address contains the word
+    if let Some(word) = &config.checksum_word {
+        if !has_checksum_word(address, word) {
+            return None;
+        }
+    }
 
-    // the last work duration in milliseconds
-    let mut work_duration_millis: u64 = 0;
+    // only proceed if the address starts with the requested prefix
+    if let Some(prefix) = &config.prefix {
+        if !has_prefix(address, prefix) {
+            return None;
+        }
+    }
 
-    // Create reusable buffers once to avoid memory leaks
-    let mut message_buffer = Buffer::builder()
-        .queue(ocl_pq.queue().clone())
-        .flags(MemFlags::new().read_write())
-        .len(4)
-        .build()?;
+    // only proceed if the address contains the requested pattern
+    if let Some((offset, pattern)) = &config.pattern {
+        if !has_pattern(address, *offset, pattern) {
+            return None;
+        }
+    }
 
-    let mut nonce_buffer = Buffer::builder()
-        .queue(ocl_pq.queue().clone())
-        .flags(MemFlags::new().read_write())
-        .len(1)
-        .build()?;
+    // only proceed if the address repeats a byte or pair for long enough
+    if let Some(min_repeat) = config.min_repeat {
+        if repeat_score(address).longest() < min_repeat as usize {
+            return None;
+        }
+    }
 
-    // Increase solutions buffer size for vectorization (64 slots)
-    let mut solutions: Vec<u64> = vec![0; 64];
-    let solutions_buffer = Buffer::builder()
-        .queue(ocl_pq.queue().clone())
-        .flags(MemFlags::new().write_only())
-        .len(64)
-        .copy_host_slice(&solutions)
-        .build()?;
+    // only proceed if the address has the requested byte at each masked position
+    if let Some(mask) = &config.mask {
+        if !has_mask(address, mask) {
+            return None;
+        }
+    }
 
-    // begin searching for addresses
-    loop {
-        // construct the 4-byte message to hash, leaving last 8 of salt empty
-        let salt = FixedBytes::<4>::random();
+    // count total, leading, and consecutive zero bytes
+    let score = score_address(address);
 
-        // Update the message buffer with new salt
-        message_buffer.write(&salt[..]).enq()?;
+    // look up the reward amount
+    let reward_amount = rewards.get(&score.reward_key(config.zero_granularity));
 
-        // reset nonce & create a buffer to view it in little-endian
-        // for more uniformly distributed nonces, we shall initialize it to a random value
-        let mut nonce: [u32; 1] = rng.gen();
-        let mut view_buf = [0; 8];
+    // only proceed if the reward is high enough, when asked for one
+    if let Some(min_reward) = config.min_reward {
+        if !reward_at_least(reward_amount.unwrap_or("0"), min_reward) {
+            return None;
+        }
+    }
 
-        // Update the nonce buffer with initial nonce
-        nonce_buffer.write(&nonce[..]).enq()?;
+    // only proceed if an efficient address has been found: one that beats the
+    // reference address in that mode, one with a long enough zero run in that
+    // mode, one below the bound in that mode, any with the prefix, pattern,
+    // repeat or mask in those modes, any meeting the thresholds of a self-check, or
+    // else one with at least three zero bytes and a known reward
+    let efficient = match (beat_score, config.min_zero_run, &config.max_address) {
+        (Some(beat_score), _, _) => score.beats(beat_score),
+        (None, Some(min_zero_run), _) => score.zero_run >= min_zero_run as usize,
+        (None, None, Some(bound)) => is_below(address, bound),
+        (None, None, None) => {
+            config.prefix.is_some()
+                || config.pattern.is_some()
+                || config.min_repeat.is_some()
+                || config.mask.is_some()
+                || (config.once && score.total >= config.total_zeroes_threshold as usize)
+                || (score.total >= 3 && reward_amount.is_some())
+        }
+    };
+    if !efficient {
+        return None;
+    }
 
-        // Clear solutions buffer before starting
-        solutions.fill(0);
-        solutions_buffer.write(&solutions[..]).enq()?;
+    // skip an address containing any denied bytes; this only runs on matches
+    if contains_any(address, &config.deny) {
+        return None;
+    }
 
-        // repeatedly enqueue kernel to search for new addresses
-        loop {
-            // build the kernel and define the type of each buffer
-            let kern = ocl_pq
-                .kernel_builder("hashMessage")
-                .arg_named("message", None::<&Buffer<u8>>)
-                .arg_named("nonce", None::<&Buffer<u32>>)
-                .arg_named("solutions", None::<&Buffer<u64>>)
-                .build()?;
+    Some(Match {
+        score,
+        reward: reward_amount,
+    })
+}
 
-            // set each buffer
-            kern.set_arg("message", Some(&message_buffer))?;
-            kern.set_arg("nonce", Some(&nonce_buffer))?;
-            kern.set_arg("solutions", &solutions_buffer)?;
+/// Whether an address meets the criteria that the kernel built by
+/// `mk_kernel_src` checks before reporting a solution.
+fn meets_kernel_criteria(config: &Config, address: &Address, score: &Score) -> bool {
+    if let Some(tag) = config.tag {
+        if address[16..] != tag {
+            return false;
+        }
+    }
+    if let Some(suffix) = &config.suffix {
+        if !has_suffix(address, suffix) {
+            return false;
+        }
+    }
 
-            // enqueue the kernel with proper work group sizing
-            unsafe { 
-                kern.cmd()
-                    .global_work_size(global_work_size)
-                    .local_work_size(local_work_size)
-                    .enq()? 
-            };
+    match (config.min_zero_run, config.max_address, &config.prefix) {
+        (Some(run), _, _) => score.zero_run >= run as usize,
+        (None, Some(bound), _) => is_below(address, &bound),
+        (None, None, Some(prefix)) => has_prefix(address, prefix),
+        (None, None, None) if config.pattern.is_some() => {
+            let (offset, pattern) = config.pattern.as_ref().unwrap();
+            has_pattern(address, *offset, pattern)
+        }
+        (None, None, None) if config.min_repeat.is_some() => {
+            repeat_score(address).longest() >= config.min_repeat.unwrap_or_default() as usize
+        }
+        (None, None, None) if config.mask.is_some() => {
+            has_mask(address, config.mask.as_deref().unwrap_or_default())
+        }
+        (None, None, None) => {
+            score.leading_at(config.zero_granularity) >= config.leading_zeroes_threshold as usize
+                || (config.total_zeroes_threshold <= 20
+                    && score.total >= config.total_zeroes_threshold as usize)
+        }
+    }
+}
 
-            // calculate the current time
-            let mut now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-            let current_time = now.as_secs() as f64;
+/// Build a kernel made by `mk_kernel_src` for `device`. When it fails to build,
+/// the error names the device and carries the OpenCL build log with the
+/// compiler's diagnostics, e.g. after an edit to `keccak256.cl` or on an
+/// unfamiliar driver.
+fn build_kernel(context: &Context, device: Device, src: String) -> ocl::Result<Program> {
+    Program::builder()
+        .devices(device)
+        .src(src)
+        .build(context)
+        .map_err(|e| {
+            let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+            format!("the kernel failed to build for {name}; OpenCL build log:\n{e}").into()
+        })
+}
 
-            // we don't want to print too fast
-            let print_output = current_time - previous_time > 0.99;
-            previous_time = current_time;
+/// Creates the OpenCL kernel source code by populating the template with the
+/// values from the Config object and the given caller and init code hash.
+fn mk_kernel_src(config: &Config, caller: &[u8; 20], init_code_hash: &[u8; 32]) -> String {
+    let mut src = String::with_capacity(2048 + KERNEL_SRC.len());
 
-            // clear the terminal screen
-            if print_output {
-                term.clear_screen()?;
+    // each `S_n` is byte n of the message hashed for each salt, which starts
+    // with the control character; the kernel fills in the salt after the
+    // caller segment, or all of it without one, from the message
+    let caller_bytes = config.caller_salt_bytes();
+    let mut salt = [0; 32];
+    salt[..caller_bytes].copy_from_slice(&caller[..caller_bytes]);
+    let message = config.preimage(init_code_hash).message(&salt);
+    let known = Preimage::INIT_CODE_HASH_OFFSET..Preimage::LEN;
+    for n in (0..Preimage::SALT_OFFSET + caller_bytes).chain(known) {
+        writeln!(src, "#define S_{n} {}u", message[n]).unwrap();
+    }
+    let lz = config.leading_zeroes_threshold;
+    match config.zero_granularity {
+        Granularity::Byte => writeln!(src, "#define LEADING_ZEROES {lz}").unwrap(),
+        Granularity::Nibble => writeln!(src, "#define LEADING_NIBBLES {lz}").unwrap(),
+    }
+    let tz = config.total_zeroes_threshold;
+    writeln!(src, "#define TOTAL_ZEROES {tz}").unwrap();
+    if let Some(run) = config.min_zero_run {
+        writeln!(src, "#define MIN_ZERO_RUN {run}").unwrap();
+    }
+    writeln!(src, "#define CALLER_BYTES {caller_bytes}").unwrap();
+    let random_bytes = config.salt_random_bytes;
+    writeln!(src, "#define RANDOM_BYTES {random_bytes}").unwrap();
+    let nonce_bytes = config.salt_nonce_bytes;
+    writeln!(src, "#define NONCE_BYTES {nonce_bytes}").unwrap();
+    if let Some(tag) = config.tag {
+        // compared byte by byte against the last four bytes of the address
+        let bytes: Vec<String> = tag.iter().map(|b| format!("{b}u")).collect();
+        writeln!(src, "#define TAG {{{}}}", bytes.join(", ")).unwrap();
+    }
+    if let Some(suffix) = &config.suffix {
+        let bytes: Vec<String> = suffix.iter().map(|b| format!("{b}u")).collect();
+        writeln!(src, "#define SUFFIX_BYTES {}", suffix.len()).unwrap();
+        writeln!(src, "#define SUFFIX {{{}}}", bytes.join(", ")).unwrap();
+    }
+    if let Some(prefix) = &config.prefix {
+        let nibbles: Vec<String> = prefix.iter().map(|n| format!("{n}u")).collect();
+        writeln!(src, "#define PREFIX_NIBBLES {}", prefix.len()).unwrap();
+        writeln!(src, "#define PREFIX {{{}}}", nibbles.join(", ")).unwrap();
+    }
+    if let Some(bound) = config.max_address {
+        let bytes: Vec<String> = bound.iter().map(|b| format!("{b}u")).collect();
+        writeln!(src, "#define MAX_ADDRESS {{{}}}", bytes.join(", ")).unwrap();
+    }
+    if let Some((offset, pattern)) = &config.pattern {
+        let bytes: Vec<String> = pattern.iter().map(|b| format!("{b}u")).collect();
+        writeln!(src, "#define PATTERN_OFFSET {offset}").unwrap();
+        writeln!(src, "#define PATTERN_BYTES {}", pattern.len()).unwrap();
+        writeln!(src, "#define PATTERN {{{}}}", bytes.join(", ")).unwrap();
+    }
+    if let Some(run) = config.min_repeat {
+        writeln!(src, "#define MIN_REPEAT {run}").unwrap();
+    }
+    if let Some(mask) = &config.mask {
+        let positions: Vec<String> = mask.iter().map(|(p, _)| format!("{p}u")).collect();
+        let bytes: Vec<String> = mask.iter().map(|(_, b)| format!("{b}u")).collect();
+        writeln!(src, "#define MASK_BYTES {}", mask.len()).unwrap();
+        writeln!(src, "#define MASK_POSITIONS {{{}}}", positions.join(", ")).unwrap();
+        writeln!(src, "#define MASK {{{}}}", bytes.join(", ")).unwrap();
+    }
 
-                // get the total runtime and parse into hours : minutes : seconds
-                let total_runtime = current_time - start_time;
-                let total_runtime_hrs = total_runtime as u64 / 3600;
-                let total_runtime_mins = (total_runtime as u64 - total_runtime_hrs * 3600) / 60;
-                let total_runtime_secs = total_runtime
-                    - (total_runtime_hrs * 3600) as f64
-                    - (total_runtime_mins * 60) as f64;
+    src.push_str(KERNEL_SRC);
 
-                // determine the number of attempts being made per second
-                // Account for 8x vectorization (each work item processes 8 nonces)
-                let work_factor = (global_work_size as u128 * 8) / 1_000_000;
-                let work_rate: u128 = work_factor * cumulative_nonce as u128;
-                if total_runtime > 0.0 {
-                    rate = 1.0 / total_runtime;
-                }
+    src
+}
 
-                // fill the buffer for viewing the properly-formatted nonce
-                LittleEndian::write_u64(&mut view_buf, (nonce[0] as u64) << 32);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-                // calculate the terminal height, defaulting to a height of ten rows
-                let height = terminal_size().map(|(_w, Height(h))| h).unwrap_or(10);
+    const FACTORY: &str = "0x0000000000ffe8b47b3e2130213b802212439497";
+    const CALLER: &str = "0x00000000000000000000000000000000000000aa";
+    const INIT_CODE_HASH: &str =
+        "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
 
-                // display information about the total runtime and work size
-                term.write_line(&format!(
-                    "total runtime: {}:{:02}:{:02} ({} cycles)\t\t\t\
-                     work size per cycle: {} (8x vectorized)",
-                    total_runtime_hrs,
-                    total_runtime_mins,
-                    total_runtime_secs,
-                    cumulative_nonce,
-                    (global_work_size * 8).separated_string(),
-                ))?;
+    /// Parse a config for the test factory, caller and init code hash.
+    fn config(options: &[&str]) -> Config {
+        let args = ["create2crunch", FACTORY, CALLER, INIT_CODE_HASH];
+        let args = args.iter().chain(options);
+        Config::new(args.map(|arg| arg.to_string())).unwrap()
+    }
 
-                // display information about the attempt rate and found solutions
-                term.write_line(&format!(
-                    "rate: {:.2} million attempts per second\t\t\t\
-                     total found this run: {}",
-                    work_rate as f64 * rate,
-                    found
-                ))?;
+    /// Run a CPU search for a few addresses starting with a zero byte, and
+    /// return the factory and caller searched with along with the solutions.
+    fn search(options: &[&str]) -> ([u8; 20], [u8; 20], Vec<FoundSalt>) {
+        let search_options = ["--prefix", "0x00", "--max-results", "4"];
+        let options: Vec<&str> = search_options.iter().chain(options).copied().collect();
+        let config = config(&options);
+        let (factory, caller) = (config.factory_address, config.callers[0]);
 
-                // display information about the current search criteria
-                term.write_line(&format!(
-                    "current search space: {}xxxxxxxx{:08x}\t\t\
-                     threshold: {} leading or {} total zeroes",
-                    hex::encode(salt),
-                    BigEndian::read_u64(&view_buf),
-                    config.leading_zeroes_threshold,
-                    config.total_zeroes_threshold
-                ))?;
+        let (tx, rx) = mpsc::channel();
+        cpu_with_sink(config, tx).unwrap();
+        (factory, caller, rx.iter().collect())
+    }
 
-                // display recently found solutions based on terminal height
-                let rows = if height < 5 { 1 } else { height as usize - 4 };
-                let last_rows: Vec<String> = found_list.iter().cloned().rev().take(rows).collect();
-                let ordered: Vec<String> = last_rows.iter().cloned().rev().collect();
-                let recently_found = &ordered.join("\n");
-                term.write_line(recently_found)?;
-            }
+    #[test]
+    fn builder_setters_are_checked_like_their_arguments() {
+        fn bytes<const N: usize>(value: &str) -> [u8; N] {
+            hex::decode(&value[2..]).unwrap().try_into().unwrap()
+        }
+        let builder = || {
+            Config::builder()
+                .factory_address(bytes(FACTORY))
+                .calling_address(bytes(CALLER))
+                .init_code_hash(bytes(INIT_CODE_HASH))
+        };
 
-            // increment the cumulative nonce (does not reset after a match)
-            cumulative_nonce += 1;
+        let built = builder()
+            .output_format(OutputFormat::Json)
+            .max_results(4)
+            .max_duration(Duration::from_millis(1500))
+            .mask(&[(0, 0x00), (19, 0xab)])
+            .deny(&[&[0xde, 0xad]])
+            .salt_split(2, 6)
+            .build()
+            .unwrap();
+        let parsed = config(&[
+            "--format",
+            "json",
+            "--max-results",
+            "4",
+            "--duration",
+            "1.5s",
+            "--mask",
+            "0:0x00,19:0xab",
+            "--deny",
+            "0xdead",
+            "--salt-split",
+            "2:6",
+        ]);
+        assert_eq!(built.output_format, parsed.output_format);
+        assert_eq!(built.max_results, parsed.max_results);
+        assert_eq!(built.max_duration, parsed.max_duration);
+        assert_eq!(built.mask, parsed.mask);
+        assert_eq!(built.deny, parsed.deny);
+        assert_eq!(
+            (built.salt_random_bytes, built.salt_nonce_bytes),
+            (parsed.salt_random_bytes, parsed.salt_nonce_bytes)
+        );
 
-            // record the start time of the work
-            let work_start_time_millis = now.as_secs() * 1000 + now.subsec_nanos() as u64 / 1000000;
+        assert!(builder().max_results(0).build().is_err());
+        assert!(builder().work_size(1).build().is_err());
+    }
 
-            // sleep for 98% of the previous work duration to conserve CPU
-            if work_duration_millis != 0 {
-                std::thread::sleep(std::time::Duration::from_millis(
-                    work_duration_millis * 980 / 1000,
-                ));
-            }
+    #[test]
+    fn found_salts_derive_their_addresses() {
+        let (factory, caller, found) = search(&[]);
+        assert_eq!(found.len(), 4);
+        for found in found {
+            assert_eq!(found.salt[..20], caller);
+            let address = create2_address(&factory, &found.salt, &found.init_code_hash);
+            assert_eq!(address, found.address);
+            assert_eq!(address[0], 0);
+        }
+    }
 
-            // read the solutions from the device
-            solutions_buffer.read(&mut solutions).enq()?;
+    #[test]
+    fn found_salts_without_a_caller_derive_their_addresses() {
+        let (factory, _, found) = search(&["--no-caller-salt"]);
+        assert_eq!(found.len(), 4);
+        for found in found {
+            let address = create2_address(&factory, &found.salt, &found.init_code_hash);
+            assert_eq!(address, found.address);
+        }
+    }
 
-            // record the end time of the work and compute how long the work took
-            now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-            work_duration_millis = (now.as_secs() * 1000 + now.subsec_nanos() as u64 / 1000000)
-                - work_start_time_millis;
+    /// The salt hashed by the kernel for the given work item, with the given
+    /// random segment and nonce, laid out as `gpu_worker` lays it out.
+    fn kernel_salt(config: &Config, segment: &[u8], work_item: u32, nonce: u64) -> [u8; 32] {
+        let caller_bytes = config.caller_salt_bytes();
+        let work_item_start = caller_bytes + segment.len();
+        let nonce_start = work_item_start + WORK_ITEM_BYTES;
 
-            // if at least one solution is found, end the loop
-            if solutions.iter().any(|&x| x != 0) {
-                break;
+        let mut salt = [0; 32];
+        salt[..caller_bytes].copy_from_slice(&config.callers[0][..caller_bytes]);
+        salt[caller_bytes..work_item_start].copy_from_slice(segment);
+        salt[work_item_start..nonce_start].copy_from_slice(&work_item.to_le_bytes());
+        salt[nonce_start..].copy_from_slice(&nonce.to_le_bytes()[..32 - nonce_start]);
+        salt
+    }
+
+    /// Run one cycle of the kernel on the first configured device, the way
+    /// `bench` does, and return the number of salts it hashed along with the
+    /// salts it reported.
+    fn kernel_salts(
+        config: &Config,
+        segment: &[u8],
+        nonce: u64,
+    ) -> ocl::Result<(u32, Vec<[u8; 32]>)> {
+        let mut salts = vec![];
+        let (hashed, _) = run_kernel(config, segment, nonce, true, |cycles, _, solutions| {
+            salts = solutions
+                .iter()
+                .filter(|&&solution| solution != 0)
+                .map(|&solution| kernel_salt(config, segment, solution as u32, nonce))
+                .collect();
+            cycles == 0
+        })?;
+        Ok((hashed as u32, salts))
+    }
+
+    /// Check that one cycle of the kernel reports exactly the salts whose
+    /// addresses meet its criteria on the CPU. Where the CPU search matches
+    /// on the same criteria, i.e. anything but zero bytes, `evaluate_address`
+    /// has to agree on every address as well.
+    fn assert_kernel_agrees_with_cpu(options: &[&str]) {
+        let config = config(options);
+        let (factory, init_code_hash) = (config.factory_address, config.init_code_hashes[0]);
+        let rewards = Reward::new();
+        let segment: Vec<u8> = (1..=config.salt_random_bytes).collect();
+        let nonce = 1;
+
+        let (hashed, mut reported) = kernel_salts(&config, &segment, nonce).unwrap();
+        let same_criteria =
+            config.prefix.is_some() || config.pattern.is_some() || config.mask.is_some();
+        let mut expected = vec![];
+        for work_item in 0..hashed {
+            let salt = kernel_salt(&config, &segment, work_item, nonce);
+            let address = create2_address(&factory, &salt, &init_code_hash);
+            let matched = meets_kernel_criteria(&config, &address, &score_address(&address));
+            if same_criteria {
+                let found = evaluate_address(&address, &rewards, &config, None);
+                assert_eq!(found.is_some(), matched, "{address} with {options:?}");
+            }
+            if matched {
+                expected.push(salt);
             }
+        }
 
-            // if no solution has yet been found, increment the nonce
-            nonce[0] += 1;
+        // past the last solution slot, the kernel drops solutions
+        assert!(
+            expected.len() < SOLUTION_SLOTS as usize,
+            "{options:?} matches too many addresses to compare"
+        );
+        reported.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(reported, expected, "with {options:?}");
+    }
 
-            // update the nonce buffer with the incremented nonce value
-            nonce_buffer.write(&nonce[..]).enq()?;
+    // compares the kernel with the CPU on the first GPU, so it only runs with
+    // `cargo test -- --ignored` on a machine that has one
+    #[test]
+    #[ignore = "needs an OpenCL GPU"]
+    fn the_kernel_reports_the_salts_the_cpu_matches() {
+        let work_size = ["--work-size", "0x100000"];
+        let cases: [&[&str]; 6] = [
+            &["0", "2", "4"],
+            &["0", "4", "255", "--granularity", "nibble"],
+            &["0", "--prefix", "0xbeef"],
+            &["0", "--prefix", "0x00", "--suffix", "0x42"],
+            &["0", "--pattern", "10:0x4242"],
+            &["0", "--mask", "0:0x00,19:0x42"],
+        ];
+        for options in cases {
+            let options: Vec<&str> = options.iter().chain(&work_size).copied().collect();
+            assert_kernel_agrees_with_cpu(&options);
         }
+    }
 
-        // iterate over each solution, first converting to a fixed array
-        for &solution in &solutions {
-            if solution == 0 {
-                continue;
-            }
+    const THREE_LEADING: Address = Address::new(hex!("0000001122334455667788991122334455667788"));
+    const FIVE_TOTAL: Address = Address::new(hex!("1100220033004400550011223344556677889911"));
+    const TWO_LEADING: Address = Address::new(hex!("0000112233445566778899112233445566778899"));
 
-            let solution = solution.to_le_bytes();
+    #[test]
+    fn addresses_with_enough_zero_bytes_are_matched_with_their_reward() {
+        let rewards = Reward::new();
+        let config = config(&[]);
 
-            let mut solution_message = [0; 85];
-            solution_message[0] = CONTROL_CHARACTER;
-            solution_message[1..21].copy_from_slice(&config.factory_address);
-            solution_message[21..41].copy_from_slice(&config.calling_address);
-            solution_message[41..45].copy_from_slice(&salt[..]);
-            solution_message[45..53].copy_from_slice(&solution);
-            solution_message[53..].copy_from_slice(&config.init_code_hash);
-
-            // create new hash object
-            let mut hash = Keccak::v256();
-
-            // update with header
-            hash.update(&solution_message);
-
-            // hash the payload and get the result
-            let mut res: [u8; 32] = [0; 32];
-            hash.finalize(&mut res);
-
-            // get the address that results from the hash
-            let address = <&Address>::try_from(&res[12..]).unwrap();
-
-            // count total and leading zero bytes
-            let mut total = 0;
-            let mut leading = 21;
-            for (i, &b) in address.iter().enumerate() {
-                if b == 0 {
-                    total += 1;
-                } else if leading == 21 {
-                    // set leading on finding non-zero byte
-                    leading = i;
-                }
-            }
-
-            let key = leading * 20 + total;
-            let reward = rewards.get(&key).unwrap_or("0");
-            let output = format!(
-                "0x{}{}{} => {} => {}",
-                hex::encode(config.calling_address),
-                hex::encode(salt),
-                hex::encode(solution),
-                address,
-                reward,
-            );
+        let found = evaluate_address(&THREE_LEADING, &rewards, &config, None).unwrap();
+        assert_eq!((found.score.leading, found.score.total), (3, 3));
+        assert_eq!(found.reward, Some("1"));
+
+        let found = evaluate_address(&FIVE_TOTAL, &rewards, &config, None).unwrap();
+        assert_eq!((found.score.leading, found.score.total), (0, 5));
+        assert_eq!(found.reward, Some("4"));
+
+        assert_eq!(
+            evaluate_address(&TWO_LEADING, &rewards, &config, None),
+            None
+        );
+    }
+
+    #[test]
+    fn addresses_below_the_minimum_reward_are_rejected() {
+        let rewards = Reward::new();
+        let config = config(&["--min-reward", "2"]);
 
-            let show = format!("{output} ({leading} / {total})");
-            found_list.push(show.to_string());
+        assert_eq!(
+            evaluate_address(&THREE_LEADING, &rewards, &config, None),
+            None
+        );
+        let found = evaluate_address(&FIVE_TOTAL, &rewards, &config, None).unwrap();
+        assert_eq!(found.reward, Some("4"));
+    }
 
-            file.lock_exclusive().expect("Couldn't lock file.");
+    #[test]
+    fn prefix_mode_matches_the_prefix_rather_than_zero_bytes() {
+        let rewards = Reward::new();
+        let config = config(&["--prefix", "0x11"]);
 
-            writeln!(&file, "{output}").expect("Couldn't write to `efficient_addresses.txt` file.");
+        assert_eq!(
+            evaluate_address(&THREE_LEADING, &rewards, &config, None),
+            None
+        );
+        let found = evaluate_address(&FIVE_TOTAL, &rewards, &config, None).unwrap();
+        assert_eq!(found.score.total, 5);
+        assert_eq!(
+            evaluate_address(&TWO_LEADING, &rewards, &config, None),
+            None
+        );
+    }
 
-            FileExt::unlock(&file).expect("Couldn't unlock file.");
-            found += 1;
+    #[test]
+    fn nonces_stop_at_the_end_of_the_nonce_segment() {
+        for nonce_bytes in [1, 4, 8] {
+            let nonce_mask = u64::MAX >> (64 - 8 * nonce_bytes);
+            assert_eq!(next_nonce(0, nonce_mask), Some(1));
+            assert_eq!(next_nonce(nonce_mask - 1, nonce_mask), Some(nonce_mask));
+            assert_eq!(next_nonce(nonce_mask, nonce_mask), None);
         }
     }
-}
 
-#[track_caller]
-fn output_file() -> File {
-    OpenOptions::new()
-        .append(true)
-        .create(true)
-        .read(true)
-        .open("efficient_addresses.txt")
-        .expect("Could not create or open `efficient_addresses.txt` file.")
-}
+    #[test]
+    fn a_search_stopped_on_the_last_nonce_resumes_with_a_fresh_segment() {
+        let config = config(&["0", "--no-caller-salt", "--salt-split", "20:8"]);
+        let mut salt = [0; 20];
+        salt[19] = 0xff;
 
-/// Creates the OpenCL kernel source code by populating the template with the
-/// values from the Config object.
-fn mk_kernel_src(config: &Config) -> String {
-    let mut src = String::with_capacity(2048 + KERNEL_SRC.len());
+        let checkpoint = checkpoint_after(&config, 0, &salt, 7, 3, 1);
+        assert_eq!((checkpoint.nonce, checkpoint.segments_used), (8, 1));
+        assert_eq!(checkpoint.salt, salt);
 
-    let factory = config.factory_address.iter();
-    let caller = config.calling_address.iter();
-    let hash = config.init_code_hash.iter();
-    let hash = hash.enumerate().map(|(i, x)| (i + 52, x));
-    for (i, x) in factory.chain(caller).enumerate().chain(hash) {
-        writeln!(src, "#define S_{} {}u", i + 1, x).unwrap();
+        let checkpoint = checkpoint_after(&config, 0, &salt, u64::MAX, 3, 1);
+        assert_eq!((checkpoint.nonce, checkpoint.segments_used), (0, 2));
+        assert_eq!(checkpoint.salt[18..], [1, 0]);
+        assert_eq!(checkpoint.cumulative_nonce, 3);
     }
-    let lz = config.leading_zeroes_threshold;
-    writeln!(src, "#define LEADING_ZEROES {lz}").unwrap();
-    let tz = config.total_zeroes_threshold;
-    writeln!(src, "#define TOTAL_ZEROES {tz}").unwrap();
 
-    src.push_str(KERNEL_SRC);
+    #[test]
+    fn captured_shapes_are_described_by_leading_zero_bytes() {
+        let rewarded: Vec<_> = (0..=2)
+            .flat_map(|leading| (5..=7).map(move |total| (leading, total)))
+            .collect();
+        assert_eq!(describe_shapes(&rewarded, &rewarded), "all of them");
 
-    src
+        let captured: Vec<_> = rewarded
+            .iter()
+            .copied()
+            .filter(|&(leading, total)| leading >= 1 || total >= 7)
+            .collect();
+        assert_eq!(
+            describe_shapes(&captured, &rewarded),
+            "0 leading and 7 total, or 1+ leading zero bytes"
+        );
+
+        let captured = [(0, 5), (0, 7), (1, 6)];
+        assert_eq!(
+            describe_shapes(&captured, &rewarded),
+            "0 leading and 5/7 total, or 1 leading and 6 total"
+        );
+    }
+
+    #[test]
+    fn every_target_is_searched_in_turn() {
+        let chunks: Vec<_> = target_chunks(3).collect();
+
+        // each target gets a chunk before any gets a second one
+        let first: Vec<_> = chunks[..3].iter().map(|(target, _)| *target).collect();
+        assert_eq!(first, [0, 1, 2]);
+
+        // and in the end, the whole segment of each is searched once
+        for target in 0..3 {
+            let mut next = 0;
+            for (_, nonces) in chunks.iter().filter(|(t, _)| *t == target) {
+                assert_eq!(*nonces.start(), next);
+                next = nonces.end() + 1;
+            }
+            assert_eq!(next, MAX_INCREMENTER + 1);
+        }
+    }
+
+    #[test]
+    fn seeded_segments_count_up() {
+        let mut segment = [0x00, 0x01, 0xff, 0xff];
+        count_up(&mut segment);
+        assert_eq!(segment, [0x00, 0x02, 0x00, 0x00]);
+
+        let mut segment = [0xff; 4];
+        count_up(&mut segment);
+        assert_eq!(segment, [0x00; 4]);
+    }
+
+    #[test]
+    fn kernel_defines_follow_the_layout_of_the_hashed_message() {
+        let config = config(&[]);
+        let (caller, init_code_hash) = (config.callers[0], config.init_code_hashes[0]);
+        let src = mk_kernel_src(&config, &caller, &init_code_hash);
+        let define = |n: usize| {
+            let name = format!("#define S_{n} ");
+            let line = src.lines().find(|line| line.starts_with(&name))?;
+            Some(line[name.len()..].to_string())
+        };
+
+        // the factory, caller and init code hash follow the control character
+        assert_eq!(define(0), Some("255u".to_string()));
+        assert_eq!(define(1), Some(format!("{}u", config.factory_address[0])));
+        assert_eq!(define(20), Some(format!("{}u", config.factory_address[19])));
+        assert_eq!(define(21), Some(format!("{}u", caller[0])));
+        assert_eq!(define(40), Some(format!("{}u", caller[19])));
+        assert_eq!(define(41), None);
+        assert_eq!(define(52), None);
+        assert_eq!(define(53), Some(format!("{}u", init_code_hash[0])));
+        assert_eq!(define(84), Some(format!("{}u", init_code_hash[31])));
+        assert_eq!(define(85), None);
+    }
 }
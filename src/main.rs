@@ -3,12 +3,103 @@ use std::env;
 use std::process;
 
 fn main() {
+    // `create2crunch selftest` checks the installation without needing a GPU
+    if env::args().nth(1).as_deref() == Some("selftest") {
+        match create2crunch::selftest() {
+            Ok(()) => println!("Self-test passed."),
+            Err(e) => {
+                eprintln!("Self-test failed: {e}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // `create2crunch estimate LEADING [TOTAL]` shows how hard a search for the
+    // given thresholds is, without searching
+    if env::args().nth(1).as_deref() == Some("estimate") {
+        let thresholds: Vec<_> = env::args()
+            .skip(2)
+            .map(|arg| arg.parse::<u8>().ok())
+            .collect();
+        let estimate = match thresholds[..] {
+            [Some(leading @ 1..=20)] => create2crunch::estimate(leading, 21),
+            [Some(leading @ 1..=20), Some(total @ 1..=20)] => {
+                create2crunch::estimate(leading, total)
+            }
+            _ => {
+                eprintln!("Usage: create2crunch estimate LEADING [TOTAL] (each from 1 to 20)");
+                process::exit(1);
+            }
+        };
+        print!("{estimate}");
+        return;
+    }
+
+    // `--list-devices` shows the devices that can be searched with, using the
+    // same listing as `check_opencl`, without needing any other arguments
+    if env::args().skip(1).any(|arg| arg == "--list-devices") {
+        match create2crunch::list_devices() {
+            Ok(platforms) => print!("{}", create2crunch::format_devices(&platforms)),
+            Err(e) => {
+                eprintln!("Couldn't list OpenCL devices: {e}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
     let config = Config::new(env::args()).unwrap_or_else(|err| {
         eprintln!("Failed parsing arguments: {err}");
         process::exit(1);
     });
 
-    if config.gpu_device == 255 {
+    // with `--no-file`, stdout only carries results, so anything else goes to
+    // stderr
+    let no_file = config.no_file;
+    let report = |line: &str| {
+        if no_file {
+            eprintln!("{line}");
+        } else {
+            println!("{line}");
+        }
+    };
+
+    // `--check` stops after making sure the search could start
+    if config.check {
+        match create2crunch::check(&config) {
+            Ok(()) => report("Check passed."),
+            Err(e) => {
+                eprintln!("Check failed: {e}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // stop cleanly on Ctrl-C, so that no result is left half-written
+    if let Err(e) = create2crunch::stop_on_ctrl_c() {
+        eprintln!("Couldn't install the Ctrl-C handler: {e}");
+    }
+
+    // `--bench N` reports the hash rate over N kernel cycles instead of searching
+    if let Some(batches) = config.bench_batches {
+        match create2crunch::bench(config, batches) {
+            Ok(result) => report(&format!(
+                "{:.2} million hashes per second ({} hashes in {:.2?})",
+                result.hashes_per_sec / 1e6,
+                result.total_hashes,
+                result.elapsed
+            )),
+            Err(e) => {
+                eprintln!("Benchmark error: {e}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if config.is_cpu() {
         if let Err(e) = create2crunch::cpu(config) {
             eprintln!("CPU application error: {e}");
             process::exit(1);
@@ -0,0 +1,80 @@
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// How often the server checks for a new connection, or for the end of the
+/// search.
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long a client gets to send its request before being answered anyway.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The counters of a GPU search as exposed with `--metrics-port`.
+pub(crate) struct Metrics {
+    pub(crate) hashes: u64,
+    pub(crate) found: u64,
+    pub(crate) hashes_per_sec: f64,
+}
+
+impl Metrics {
+    /// Render the metrics in the Prometheus text exposition format.
+    fn render(&self) -> String {
+        format!(
+            "# HELP create2crunch_hashes_total Hashes computed this run.\n\
+             # TYPE create2crunch_hashes_total counter\n\
+             create2crunch_hashes_total {}\n\
+             # HELP create2crunch_found_total Addresses found this run.\n\
+             # TYPE create2crunch_found_total counter\n\
+             create2crunch_found_total {}\n\
+             # HELP create2crunch_hashrate Recent hashes per second.\n\
+             # TYPE create2crunch_hashrate gauge\n\
+             create2crunch_hashrate {}\n",
+            self.hashes, self.found, self.hashes_per_sec
+        )
+    }
+}
+
+/// Answer every request made to `listener` with the current `metrics` until
+/// `done` is set. Any path is answered, and failed connections are dropped
+/// without affecting the search.
+pub(crate) fn serve(
+    listener: &TcpListener,
+    done: &AtomicBool,
+    metrics: impl Fn() -> Metrics,
+) -> io::Result<()> {
+    // poll rather than block, so that the server ends along with the search
+    listener.set_nonblocking(true)?;
+    while !done.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let _ = respond(stream, &metrics().render());
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+            Err(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream, body: &str) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+    // the request itself doesn't matter, but reading it keeps clients from
+    // seeing the connection reset before they are done sending
+    let mut request = [0; 1024];
+    let _ = stream.read(&mut request);
+
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\n\
+         Content-Type: text/plain; version=0.0.4\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
@@ -0,0 +1,793 @@
+use alloy_primitives::{hex, Address};
+use fs4::FileExt;
+use rustc_hash::FxHashSet;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File, OpenOptions};
+use std::hash::Hasher as _;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{Create2Error, Score};
+
+/// Results with at least this many leading zero bytes share the top tier file.
+const TOP_TIER: usize = 6;
+
+/// How many salts [`SeenSalts`] remembers before starting over (8 MiB worth).
+const SEEN_SALTS_CAP: usize = 1 << 20;
+
+/// How each solution is written to the results file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// `salt => address => reward => description`, followed by any optional
+    /// columns.
+    #[default]
+    Text,
+    /// One [`FoundRecord`] JSON object per line.
+    Json,
+    /// One [`ProofBundle`] JSON object per line.
+    Bundle,
+    /// One Solidity declaration per line, as made by [`solidity_declaration`].
+    Solidity,
+}
+
+impl OutputFormat {
+    /// Parse an output format as given on the command line.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            "bundle" => Some(Self::Bundle),
+            "solidity" => Some(Self::Solidity),
+            _ => None,
+        }
+    }
+
+    /// The name of the output format as given on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Json => "json",
+            Self::Bundle => "bundle",
+            Self::Solidity => "solidity",
+        }
+    }
+}
+
+/// When results held back with `--flush-every` are written out: once so many
+/// of them are waiting, or once the first of them has waited so long.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlushPolicy {
+    Count(u32),
+    Interval(Duration),
+}
+
+impl FlushPolicy {
+    /// Parse a flush policy as given on the command line: a plain number of
+    /// results (e.g. `100`), or a span of time with its unit (e.g. `10s`).
+    pub fn parse(value: &str) -> Option<Self> {
+        if value.bytes().all(|b| b.is_ascii_digit()) {
+            return value
+                .parse()
+                .ok()
+                .filter(|&count| count > 0)
+                .map(Self::Count);
+        }
+        crate::parse_duration(value).map(Self::Interval)
+    }
+}
+
+/// A solution as sent by [`cpu_with_sink`](crate::cpu_with_sink), or yielded by
+/// [`cpu_iter`](crate::cpu_iter).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FoundSalt {
+    /// The full 32-byte salt exactly as passed to CREATE2, starting with the
+    /// caller it is for unless the search leaves the caller out of the salt.
+    pub salt: [u8; 32],
+    /// The init code hash the address is for.
+    pub init_code_hash: [u8; 32],
+    /// The address the salt results in.
+    pub address: Address,
+    /// The leading, total, and consecutive zero bytes of the address.
+    pub score: Score,
+    /// The reward (approximate rarity) of the address, `"0"` if it has none.
+    pub reward: String,
+}
+
+/// A found solution as written with `--format json`. The reward is a string
+/// as it can exceed the integers that JSON parsers commonly handle exactly.
+#[derive(Clone, Debug, Serialize)]
+pub struct FoundRecord {
+    pub salt: String,
+    pub address: String,
+    pub leading_zeros: usize,
+    pub total_zeros: usize,
+    pub reward: String,
+    /// The init code hash the address is for, only given when searching with
+    /// several of them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub init_code_hash: Option<String>,
+}
+
+impl FoundRecord {
+    pub fn new(salt: &[u8; 32], address: &Address, score: &Score, reward: &str) -> Self {
+        Self {
+            salt: format!("0x{}", hex::encode(salt)),
+            address: address.to_string(),
+            leading_zeros: score.leading,
+            total_zeros: score.total,
+            reward: reward.to_string(),
+            init_code_hash: None,
+        }
+    }
+
+    /// Render the record as a single line of JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("found records are always serializable")
+    }
+}
+
+/// Everything needed to independently recompute the address of a solution,
+/// along with the version of this crate that produced it.
+#[derive(Clone, Debug, Serialize)]
+pub struct ProofBundle {
+    pub factory: String,
+    /// The caller the salt is for, left out when the salt has no caller
+    /// segment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caller: Option<String>,
+    pub salt: String,
+    pub init_code_hash: String,
+    pub address: String,
+    pub generator: String,
+}
+
+impl ProofBundle {
+    pub fn new(
+        factory: &[u8; 20],
+        salt: &[u8; 32],
+        init_code_hash: &[u8; 32],
+        address: &Address,
+    ) -> Self {
+        Self {
+            factory: format!("0x{}", hex::encode(factory)),
+            caller: Some(format!("0x{}", hex::encode(&salt[..20]))),
+            salt: format!("0x{}", hex::encode(salt)),
+            init_code_hash: format!("0x{}", hex::encode(init_code_hash)),
+            address: address.to_string(),
+            generator: concat!("create2crunch ", env!("CARGO_PKG_VERSION")).to_string(),
+        }
+    }
+
+    /// Render the bundle as a single line of JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("proof bundles are always serializable")
+    }
+}
+
+/// Declare a salt in Solidity, with the checksummed address it deploys to (and
+/// the init code hash that is for, if given) in a comment, ready to be pasted
+/// into a deploy script, e.g.
+/// `bytes32 salt = 0x00…2a; // => 0x0000000000AbC…`.
+pub fn solidity_declaration(
+    salt: &[u8; 32],
+    address: &[u8; 20],
+    init_code_hash: Option<&[u8; 32]>,
+) -> String {
+    let salt = hex::encode(salt);
+    let address = Address::from(*address).to_checksum(None);
+    match init_code_hash {
+        Some(hash) => format!(
+            "bytes32 salt = 0x{salt}; // => {address} (init code hash 0x{})",
+            hex::encode(hash)
+        ),
+        None => format!("bytes32 salt = 0x{salt}; // => {address}"),
+    }
+}
+
+/// Render an address for the results, checksummed unless `--no-checksum` was
+/// given.
+pub(crate) fn display_address(address: &[u8; 20], checksum: bool) -> String {
+    if checksum {
+        Address::from(*address).to_checksum(None)
+    } else {
+        format!("0x{}", hex::encode(address))
+    }
+}
+
+/// Render an address the way wallets and explorers commonly truncate it, the
+/// first and last four hex digits of its checksummed form (`0x0000…1234`).
+pub fn truncated_display(address: &Address) -> String {
+    let checksummed = address.to_string();
+    format!("{}…{}", &checksummed[..6], &checksummed[38..])
+}
+
+/// Name of the file that results with `leading` zero bytes are written to when
+/// writing a separate file per tier, e.g. `found_4.txt` or `found_6plus.txt`.
+pub fn tier_file_name(leading: usize) -> String {
+    if leading >= TOP_TIER {
+        format!("found_{TOP_TIER}plus.txt")
+    } else {
+        format!("found_{leading}.txt")
+    }
+}
+
+/// Where found solutions are written: either the single results file, which
+/// with `--max-file-size` is rotated as it grows, or one file per tier of
+/// leading zero bytes next to where the results file would be (each opened on
+/// first use), or stdout with `--no-file`. Every write to a file takes an
+/// exclusive lock on it.
+pub(crate) enum ResultWriter {
+    Single(File),
+    Rotating(RotatingFile),
+    Tiered(PathBuf, Option<String>, Mutex<Vec<Option<File>>>),
+    Stdout,
+}
+
+impl ResultWriter {
+    /// Open the results file, rotating it once it grows past `max_size` if
+    /// given, or prepare the tier files, starting the output of this run with
+    /// `header` if given (in each tier file as it is opened, and in each fresh
+    /// file after a rotation). Fails if any of these files can't be created or
+    /// opened for appending.
+    pub(crate) fn new(
+        path: &Path,
+        tiered: bool,
+        max_size: Option<u64>,
+        header: Option<String>,
+    ) -> Result<Self, Create2Error> {
+        // tier files are only created once a solution of their tier is found,
+        // and a fresh file once the results file is rotated, so check up front
+        // that they can be rather than fail on a solution later
+        if tiered || max_size.is_some() {
+            check_can_create_next_to(path)?;
+        }
+        if tiered {
+            for tier in 0..=TOP_TIER {
+                let tier_path = path.with_file_name(tier_file_name(tier));
+                if tier_path.exists() {
+                    output_file(tier_path)?;
+                }
+            }
+            let tiers = (0..=TOP_TIER).map(|_| None).collect();
+            return Ok(Self::Tiered(path.to_path_buf(), header, Mutex::new(tiers)));
+        }
+
+        let file = output_file(path)?;
+        if let Some(header) = &header {
+            write_locked(&file, header);
+        }
+        Ok(match max_size {
+            Some(max_size) => Self::Rotating(RotatingFile {
+                path: path.to_path_buf(),
+                max_size,
+                header,
+                file: Mutex::new(file),
+            }),
+            None => Self::Single(file),
+        })
+    }
+
+    /// Print solutions to stdout, one per line, rather than write them to any
+    /// file. The `header`, if given, goes to stderr with everything else that
+    /// isn't a solution.
+    pub(crate) fn stdout(header: Option<String>) -> Self {
+        if let Some(header) = &header {
+            eprintln!("{header}");
+        }
+        Self::Stdout
+    }
+
+    /// Append a line for a solution with `leading` zero bytes.
+    pub(crate) fn write(&self, leading: usize, line: &str) {
+        self.write_all(&[(leading, line.to_string())]);
+    }
+
+    /// Append the lines for several solutions, each with its leading zero
+    /// bytes, taking the lock on each file they go to only once.
+    pub(crate) fn write_all(&self, lines: &[(usize, String)]) {
+        match self {
+            Self::Single(file) => write_lines_locked(file, lines.iter().map(|line| &line.1)),
+            Self::Rotating(file) => file.write_lines(lines.iter().map(|line| &line.1)),
+            Self::Tiered(path, header, tiers) => {
+                let mut tiers = tiers.lock().unwrap();
+                for (tier, file) in tiers.iter_mut().enumerate() {
+                    let mut lines = lines
+                        .iter()
+                        .filter(|line| line.0.min(TOP_TIER) == tier)
+                        .map(|line| &line.1)
+                        .peekable();
+                    if lines.peek().is_none() {
+                        continue;
+                    }
+                    let file = file.get_or_insert_with(|| {
+                        let path = path.with_file_name(tier_file_name(tier));
+                        let file = output_file(path).unwrap_or_else(|e| panic!("{e}"));
+                        if let Some(header) = header {
+                            write_locked(&file, header);
+                        }
+                        file
+                    });
+                    write_lines_locked(file, lines);
+                }
+            }
+            Self::Stdout => lines.iter().for_each(|line| println!("{}", line.1)),
+        }
+    }
+
+    /// Make sure every line written so far has reached the disk.
+    pub(crate) fn flush(&self) {
+        let sync = |file: &File| file.sync_all().expect("Couldn't flush results file.");
+        match self {
+            Self::Single(file) => sync(file),
+            Self::Rotating(file) => sync(&file.file.lock().unwrap()),
+            Self::Tiered(_, _, tiers) => tiers.lock().unwrap().iter().flatten().for_each(sync),
+            Self::Stdout => io::stdout().flush().expect("Couldn't flush stdout."),
+        }
+    }
+
+    /// Rewrite every results file with its lines sorted by reward, highest
+    /// first. Lines without a reward (e.g. proof bundles) sort last, and the
+    /// `#` lines of run headers are kept first.
+    pub(crate) fn sort(&self) {
+        match self {
+            Self::Single(file) => sort_locked(file),
+            Self::Rotating(file) => sort_locked(&file.file.lock().unwrap()),
+            Self::Tiered(_, _, tiers) => {
+                tiers.lock().unwrap().iter().flatten().for_each(sort_locked)
+            }
+            Self::Stdout => {}
+        }
+    }
+}
+
+/// The results file with `--max-file-size`: once a write takes it past the
+/// size, it is renamed to the first free name such as
+/// `efficient_addresses.1.txt`, and a fresh file is started in its place.
+pub(crate) struct RotatingFile {
+    path: PathBuf,
+    max_size: u64,
+    header: Option<String>,
+    file: Mutex<File>,
+}
+
+impl RotatingFile {
+    fn write_lines<S: AsRef<str>>(&self, lines: impl IntoIterator<Item = S>) {
+        let mut file = self.file.lock().unwrap();
+
+        // another run writing to the same results file may have rotated it
+        // since it was opened here, so only write once the lock is held on
+        // the file that is at the path now
+        loop {
+            file.lock_exclusive().expect("Couldn't lock file.");
+            if is_file_at(&file, &self.path) {
+                break;
+            }
+            FileExt::unlock(&*file).expect("Couldn't unlock file.");
+            *file = output_file(&self.path).unwrap_or_else(|e| panic!("{e}"));
+        }
+
+        for line in lines {
+            writeln!(file, "{}", line.as_ref()).expect("Couldn't write to results file.");
+        }
+
+        // rename the file while still holding the lock on it, so that no other
+        // run writes to it in the meantime, and carry on with a fresh one
+        let size = file.metadata().map_or(0, |metadata| metadata.len());
+        if size > self.max_size {
+            fs::rename(&self.path, rotated_path(&self.path))
+                .expect("Couldn't rotate results file.");
+            let fresh = output_file(&self.path).unwrap_or_else(|e| panic!("{e}"));
+            if let Some(header) = &self.header {
+                write_locked(&fresh, header);
+            }
+            FileExt::unlock(&*file).expect("Couldn't unlock file.");
+            *file = fresh;
+        } else {
+            FileExt::unlock(&*file).expect("Couldn't unlock file.");
+        }
+    }
+}
+
+/// A [`ResultWriter`] that holds results back with `--flush-every`, to write
+/// them out together as its [`FlushPolicy`] says, or writes each one as it
+/// comes without one. Anything still held back is written out when it is
+/// flushed or dropped.
+pub(crate) struct BufferedResults {
+    writer: ResultWriter,
+    policy: Option<FlushPolicy>,
+    pending: Mutex<Pending>,
+}
+
+/// The lines held back, each with its leading zero bytes, and since when.
+#[derive(Default)]
+struct Pending {
+    lines: Vec<(usize, String)>,
+    since: Option<Instant>,
+}
+
+impl BufferedResults {
+    pub(crate) fn new(writer: ResultWriter, policy: Option<FlushPolicy>) -> Self {
+        Self {
+            writer,
+            policy,
+            pending: Mutex::default(),
+        }
+    }
+
+    /// Write or hold back a line for a solution with `leading` zero bytes.
+    pub(crate) fn write(&self, leading: usize, line: &str) {
+        let Some(policy) = self.policy else {
+            return self.writer.write(leading, line);
+        };
+        let mut pending = self.pending.lock().unwrap();
+        pending.lines.push((leading, line.to_string()));
+        pending.since.get_or_insert_with(Instant::now);
+        if let FlushPolicy::Count(count) = policy {
+            if pending.lines.len() >= count as usize {
+                self.write_pending(&mut pending);
+            }
+        }
+    }
+
+    /// Write out the lines held back once the first of them has waited for
+    /// the interval of the policy, to be called every so often by the search.
+    pub(crate) fn flush_due(&self) {
+        if let Some(FlushPolicy::Interval(interval)) = self.policy {
+            let mut pending = self.pending.lock().unwrap();
+            if pending
+                .since
+                .map_or(false, |since| since.elapsed() >= interval)
+            {
+                self.write_pending(&mut pending);
+            }
+        }
+    }
+
+    /// Write out every line held back, and make sure it has reached the disk.
+    pub(crate) fn flush(&self) {
+        self.write_pending(&mut self.pending.lock().unwrap());
+        self.writer.flush();
+    }
+
+    /// Sort every results file, as [`ResultWriter::sort`] does.
+    pub(crate) fn sort(&self) {
+        self.write_pending(&mut self.pending.lock().unwrap());
+        self.writer.sort();
+    }
+
+    fn write_pending(&self, pending: &mut Pending) {
+        let Pending { lines, .. } = std::mem::take(pending);
+        if !lines.is_empty() {
+            self.writer.write_all(&lines);
+        }
+    }
+}
+
+impl Drop for BufferedResults {
+    fn drop(&mut self) {
+        if let Ok(pending) = self.pending.get_mut() {
+            let Pending { lines, .. } = std::mem::take(pending);
+            if !lines.is_empty() {
+                self.writer.write_all(&lines);
+            }
+        }
+    }
+}
+
+/// The salts recorded so far in a run, so that a solution reported twice (by
+/// two devices, or by a cycle searched again after resuming) is only written
+/// once. Only a 64-bit hash of each salt is kept, and the set is cleared once
+/// it holds `SEEN_SALTS_CAP` of them, so that a long run can't exhaust memory.
+pub(crate) struct SeenSalts(Mutex<FxHashSet<u64>>);
+
+impl SeenSalts {
+    pub(crate) fn new() -> Self {
+        Self(Mutex::new(FxHashSet::default()))
+    }
+
+    /// Remember `salt`, returning whether it is new to this run.
+    pub(crate) fn insert(&self, salt: &[u8; 32]) -> bool {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(salt);
+
+        let mut seen = self.0.lock().unwrap();
+        if seen.len() >= SEEN_SALTS_CAP {
+            seen.clear();
+        }
+        seen.insert(hasher.finish())
+    }
+}
+
+/// A short log of the milestones of a run: each find that is rarer than every
+/// find before it, and every find in the top tier.
+pub(crate) struct Highlights {
+    file: File,
+    best: Mutex<Option<Score>>,
+}
+
+impl Highlights {
+    pub(crate) fn new(path: &Path) -> Result<Self, Create2Error> {
+        Ok(Self {
+            file: output_file(path)?,
+            best: Mutex::new(None),
+        })
+    }
+
+    /// Record the line for a solution if it is a milestone.
+    pub(crate) fn record(&self, score: &Score, line: &str) {
+        let mut best = self.best.lock().unwrap();
+        if best.map_or(true, |best| score.beats(&best)) {
+            *best = Some(*score);
+            write_locked(&self.file, &format!("new best: {line}"));
+        } else if score.leading >= TOP_TIER {
+            write_locked(&self.file, line);
+        }
+    }
+}
+
+/// A log of the search coverage of a run: the caller and random salt segment
+/// used by each outer iteration and the range of nonces searched with them.
+pub(crate) struct Trace(File);
+
+impl Trace {
+    pub(crate) fn new(path: &Path) -> Result<Self, Create2Error> {
+        output_file(path).map(Self)
+    }
+
+    /// Record that every nonce in `nonces` was searched with `caller` and
+    /// `random_segment`.
+    pub(crate) fn record(
+        &self,
+        caller: &[u8; 20],
+        random_segment: &[u8],
+        nonces: RangeInclusive<u64>,
+    ) {
+        let (start, end) = nonces.into_inner();
+        let (caller, segment) = (hex::encode(caller), hex::encode(random_segment));
+        write_locked(
+            &self.0,
+            &format!("0x{caller} 0x{segment} {start:#x}..={end:#x}"),
+        );
+    }
+}
+
+fn write_locked(file: &File, line: &str) {
+    write_lines_locked(file, [line]);
+}
+
+fn write_lines_locked<S: AsRef<str>>(mut file: &File, lines: impl IntoIterator<Item = S>) {
+    // create a lock on the file before writing
+    file.lock_exclusive().expect("Couldn't lock file.");
+
+    // write the results to file
+    for line in lines {
+        writeln!(file, "{}", line.as_ref()).expect("Couldn't write to results file.");
+    }
+
+    // release the file lock
+    FileExt::unlock(file).expect("Couldn't unlock file.");
+}
+
+fn sort_locked(mut file: &File) {
+    file.lock_exclusive().expect("Couldn't lock file.");
+
+    // read back every line written so far; this is done in memory, as even a
+    // long run rarely produces more than a few megabytes of results
+    let mut contents = String::new();
+    file.seek(SeekFrom::Start(0))
+        .and_then(|_| file.read_to_string(&mut contents))
+        .expect("Couldn't read back results file.");
+    let lines = sorted_lines(&contents);
+
+    // the file is opened for appending, so truncating it is all it takes to
+    // write the sorted lines from the start
+    file.set_len(0).expect("Couldn't truncate results file.");
+    for line in lines {
+        writeln!(file, "{line}").expect("Couldn't write to results file.");
+    }
+
+    FileExt::unlock(file).expect("Couldn't unlock file.");
+}
+
+/// Sort result lines by reward, highest first, within each run: the results
+/// stay under the `#` header of the run that found them (with `--annotate`),
+/// rather than being sorted together with those of other runs.
+fn sorted_lines(contents: &str) -> Vec<&str> {
+    fn by_reward(results: &mut [&str]) {
+        results.sort_by_key(|line| {
+            let reward = line.split(" => ").nth(2);
+            Reverse(reward.and_then(|r| r.parse::<u128>().ok()))
+        });
+    }
+
+    let mut sorted = Vec::new();
+    let mut results = Vec::new();
+    for line in contents.lines() {
+        if line.starts_with('#') {
+            by_reward(&mut results);
+            sorted.append(&mut results);
+            sorted.push(line);
+        } else {
+            results.push(line);
+        }
+    }
+    by_reward(&mut results);
+    sorted.append(&mut results);
+    sorted
+}
+
+/// Render a point in time as a UTC date and time, e.g. `2024-05-01 13:45:00 UTC`.
+pub(crate) fn utc_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let (days, secs) = ((secs / 86_400) as i64, secs % 86_400);
+
+    // the civil date of a day count since 1970-01-01, in eras of 400 years
+    // starting on the first of March (Howard Hinnant's `civil_from_days`)
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Whether `file` is still the one at `path`, rather than one that has been
+/// renamed away. Only Unix tells files apart; elsewhere, any file at `path`
+/// is taken to be the same one.
+#[cfg(unix)]
+fn is_file_at(file: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (file.metadata(), fs::metadata(path)) {
+        (Ok(open), Ok(at_path)) => open.dev() == at_path.dev() && open.ino() == at_path.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_file_at(_file: &File, path: &Path) -> bool {
+    path.exists()
+}
+
+/// The first free name to rotate `path` to, numbering from 1 between its stem
+/// and its extension, e.g. `efficient_addresses.1.txt`.
+fn rotated_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = match path.extension() {
+        Some(extension) => format!(".{}", extension.to_string_lossy()),
+        None => String::new(),
+    };
+    (1..)
+        .map(|n| path.with_file_name(format!("{stem}.{n}{extension}")))
+        .find(|rotated| !rotated.exists())
+        .unwrap()
+}
+
+/// Replace `path` with `contents` in a single step, through a temporary file
+/// and a rename, so that a reader never sees a partial write and a write cut
+/// short can't leave a corrupt file behind.
+pub(crate) fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let partial = path.with_extension("partial");
+    fs::write(&partial, contents)?;
+    fs::rename(partial, path)
+}
+
+/// Check that files can be created in the directory of `path`, by creating and
+/// removing an empty one there.
+fn check_can_create_next_to(path: &Path) -> Result<(), Create2Error> {
+    let probe = path.with_file_name(format!(".create2crunch-{}.probe", std::process::id()));
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+        .map_err(|error| Create2Error::OutputFile {
+            path: probe.clone(),
+            error,
+        })?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
+fn output_file(path: impl AsRef<Path>) -> Result<File, Create2Error> {
+    let path = path.as_ref();
+    OpenOptions::new()
+        .append(true)
+        .create(true)
+        .read(true)
+        .open(path)
+        .map_err(|error| Create2Error::OutputFile {
+            path: path.to_path_buf(),
+            error,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tier_files_that_cannot_be_created_fail_up_front() {
+        let path = std::env::temp_dir()
+            .join(format!("create2crunch-missing-{}", std::process::id()))
+            .join("efficient_addresses.txt");
+        assert!(ResultWriter::new(&path, true, None, None).is_err());
+        assert!(ResultWriter::new(&path, false, Some(1024), None).is_err());
+    }
+
+    #[test]
+    fn duplicate_salts_are_written_once() {
+        let path = std::env::temp_dir().join(format!(
+            "create2crunch-seen-salts-{}.txt",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let results = ResultWriter::new(&path, false, None, None).unwrap();
+        let seen = SeenSalts::new();
+        let (first, second) = ([1; 32], [2; 32]);
+        for salt in [first, first, second, first, second] {
+            if seen.insert(&salt) {
+                results.write(0, &format!("0x{}", hex::encode(salt)));
+            }
+        }
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                format!("0x{}", hex::encode(first)),
+                format!("0x{}", hex::encode(second))
+            ]
+        );
+    }
+
+    #[test]
+    fn results_are_sorted_within_each_run() {
+        let contents = "\
+            0x01 => 0x01 => 5\n\
+            # run 1\n\
+            0x02 => 0x02 => 1\n\
+            0x03 => 0x03 => 9\n\
+            # run 2\n\
+            # started now\n\
+            0x04 => 0x04 => 3\n\
+            0x05 => 0x05 => 7\n";
+        assert_eq!(
+            sorted_lines(contents),
+            [
+                "0x01 => 0x01 => 5",
+                "# run 1",
+                "0x03 => 0x03 => 9",
+                "0x02 => 0x02 => 1",
+                "# run 2",
+                "# started now",
+                "0x05 => 0x05 => 7",
+                "0x04 => 0x04 => 3",
+            ]
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use alloy_primitives::Address;
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::{SaltHasher, CONTROL_CHARACTER};
+
+/// Builds the message hashed to get the address a salt deploys to. By default
+/// this is the 85-byte CREATE2 message, `0xff ++ factory ++ salt ++
+/// init_code_hash`; the control byte it starts with may be changed for other
+/// salted-deployment schemes with the same layout. Both the CPU search and the
+/// kernel lay out the message at the offsets given here.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Preimage {
+    control_character: u8,
+    factory: [u8; 20],
+    init_code_hash: [u8; 32],
+}
+
+impl Preimage {
+    /// Where the factory address starts, after the control byte.
+    pub const FACTORY_OFFSET: usize = 1;
+    /// Where the salt starts, after the factory address.
+    pub const SALT_OFFSET: usize = Self::FACTORY_OFFSET + 20;
+    /// Where the init code hash starts, after the salt.
+    pub const INIT_CODE_HASH_OFFSET: usize = Self::SALT_OFFSET + 32;
+    /// The length of the whole message.
+    pub const LEN: usize = Self::INIT_CODE_HASH_OFFSET + 32;
+    /// The bytes of the salt that the CPU search hashes as part of the header,
+    /// ahead of the 6-byte nonce segment.
+    pub const SALT_HEAD_LEN: usize = 32 - 6;
+    /// The length of that header, which ends with the head of the salt.
+    pub const HEADER_LEN: usize = Self::SALT_OFFSET + Self::SALT_HEAD_LEN;
+
+    /// The CREATE2 message for the given factory and init code hash.
+    pub fn new(factory: &[u8; 20], init_code_hash: &[u8; 32]) -> Self {
+        Self {
+            control_character: CONTROL_CHARACTER,
+            factory: *factory,
+            init_code_hash: *init_code_hash,
+        }
+    }
+
+    /// Start the message with another control byte than CREATE2's `0xff`.
+    pub fn control_character(mut self, control_character: u8) -> Self {
+        self.control_character = control_character;
+        self
+    }
+
+    /// The whole message for `salt`.
+    pub fn message(&self, salt: &[u8; 32]) -> [u8; Self::LEN] {
+        let mut message = [0; Self::LEN];
+        message[0] = self.control_character;
+        message[Self::FACTORY_OFFSET..Self::SALT_OFFSET].copy_from_slice(&self.factory);
+        message[Self::SALT_OFFSET..Self::INIT_CODE_HASH_OFFSET].copy_from_slice(salt);
+        message[Self::INIT_CODE_HASH_OFFSET..].copy_from_slice(&self.init_code_hash);
+        message
+    }
+
+    /// The message up to the end of `salt_head`, which the CPU search hashes
+    /// once for every salt that starts with it.
+    pub fn header(&self, salt_head: &[u8; Self::SALT_HEAD_LEN]) -> [u8; Self::HEADER_LEN] {
+        let mut header = [0; Self::HEADER_LEN];
+        header[0] = self.control_character;
+        header[Self::FACTORY_OFFSET..Self::SALT_OFFSET].copy_from_slice(&self.factory);
+        header[Self::SALT_OFFSET..].copy_from_slice(salt_head);
+        header
+    }
+
+    /// A [`SaltHasher`] for the salts that start with `salt_head`.
+    pub fn hasher(&self, salt_head: &[u8; Self::SALT_HEAD_LEN]) -> SaltHasher {
+        SaltHasher::new(&self.header(salt_head), &self.init_code_hash)
+    }
+
+    /// The address that `salt` deploys to: the last 20 bytes of the hash of
+    /// the message.
+    pub fn address(&self, salt: &[u8; 32]) -> Address {
+        let mut hash = Keccak::v256();
+        hash.update(&self.message(salt));
+
+        let mut res: [u8; 32] = [0; 32];
+        hash.finalize(&mut res);
+        Address::from_slice(&res[12..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FACTORY: [u8; 20] = [0x11; 20];
+    const INIT_CODE_HASH: [u8; 32] = [0x33; 32];
+
+    /// A salt whose bytes are all different, to tell apart where each one lands.
+    fn salt() -> [u8; 32] {
+        std::array::from_fn(|i| 0x40 + i as u8)
+    }
+
+    #[test]
+    fn the_default_message_is_the_85_bytes_hashed_for_create2() {
+        let preimage = Preimage::new(&FACTORY, &INIT_CODE_HASH);
+        let salt = salt();
+        let message = preimage.message(&salt);
+
+        let expected = [&[0xff][..], &FACTORY, &salt, &INIT_CODE_HASH].concat();
+        assert_eq!(message.len(), 85);
+        assert_eq!(message[..], expected[..]);
+        assert_eq!(message[Preimage::FACTORY_OFFSET..][..20], FACTORY);
+        assert_eq!(message[Preimage::SALT_OFFSET..][..32], salt);
+        assert_eq!(message[Preimage::INIT_CODE_HASH_OFFSET..], INIT_CODE_HASH);
+
+        // the header hashed by the CPU search is the first 47 bytes of it, and
+        // hashing the rest after it gives the same address
+        let salt_head = salt[..Preimage::SALT_HEAD_LEN].try_into().unwrap();
+        assert_eq!(preimage.header(&salt_head)[..], message[..47]);
+        let hasher = preimage.hasher(&salt_head);
+        let address = hasher.address(&salt[Preimage::SALT_HEAD_LEN..]);
+        assert_eq!(address, preimage.address(&salt));
+    }
+
+    #[test]
+    fn another_control_character_only_changes_the_first_byte() {
+        let create2 = Preimage::new(&FACTORY, &INIT_CODE_HASH);
+        let other = create2.control_character(0xfe);
+        let salt = salt();
+
+        let (message, other_message) = (create2.message(&salt), other.message(&salt));
+        assert_eq!(other_message[0], 0xfe);
+        assert_eq!(other_message[1..], message[1..]);
+        assert_ne!(other.address(&salt), create2.address(&salt));
+    }
+}
@@ -0,0 +1,38 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The hash rate over a sliding window of recent samples, so that it follows
+/// the current throughput rather than the average over the whole run.
+pub(crate) struct RollingRate {
+    window: Duration,
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl RollingRate {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Record the total number of hashes computed as of `now`, and get the
+    /// rate in hashes per second over about the last `window`.
+    pub(crate) fn update(&mut self, now: Instant, hashes: u64) -> f64 {
+        self.samples.push_back((now, hashes));
+
+        // keep the newest sample that is at least a window old as the start of
+        // the window, dropping any older ones
+        while self.samples.len() > 2 && now.duration_since(self.samples[1].0) >= self.window {
+            self.samples.pop_front();
+        }
+
+        let (start, start_hashes) = self.samples[0];
+        let elapsed = now.duration_since(start).as_secs_f64();
+        if elapsed > 0.0 {
+            (hashes - start_hashes) as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+}
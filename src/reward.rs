@@ -1,12 +1,17 @@
 use rustc_hash::FxHashMap;
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+
+use crate::{Granularity, Score};
 
 pub struct Reward {
-    reward: FxHashMap<usize, &'static str>,
+    reward: FxHashMap<usize, Cow<'static, str>>,
 }
 
 impl Reward {
     pub fn new() -> Self {
-        let reward = FxHashMap::from_iter([
+        let reward = [
             (5, "4"),
             (6, "454"),
             (7, "57926"),
@@ -207,12 +212,207 @@ impl Reward {
             (379, "170474140766654103026661251472666657794"),
             (399, "340282366920938463463374607431768211456"),
             (420, "87112285931760246646623899502532662132736"),
-        ]);
-        Reward { reward }
+        ]
+        .into_iter()
+        .map(|(key, value)| (key, Cow::Borrowed(value)))
+        .collect();
+        Reward { reward }.with_nibble_keys()
+    }
+
+    /// Load a table of rewards from a file with a `leading total value` entry
+    /// per line, e.g. `4 6 57926` for addresses with four leading and six total
+    /// zero bytes. Blank lines and lines starting with `#` are skipped.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("couldn't read reward table `{}`: {e}", path.display()))?;
+
+        let mut reward = FxHashMap::default();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |reason: &str| format!("{}:{}: {reason}", path.display(), i + 1);
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [leading, total, value] = fields[..] else {
+                return Err(invalid("expected `leading total value`"));
+            };
+            let (Ok(leading), Ok(total)) = (leading.parse::<usize>(), total.parse::<usize>())
+            else {
+                return Err(invalid(
+                    "leading and total zero bytes must be whole numbers",
+                ));
+            };
+            if leading > 20 || total > 20 {
+                return Err(invalid(
+                    "leading and total zero bytes must be within 0..=20",
+                ));
+            }
+            if leading > total {
+                return Err(invalid("there can't be more leading than total zero bytes"));
+            }
+            if !value.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid("the value must be a whole number"));
+            }
+
+            let score = Score {
+                leading,
+                total,
+                zero_run: 0,
+                leading_nibbles: 2 * leading,
+            };
+            if reward
+                .insert(
+                    score.reward_key(Granularity::Byte),
+                    Cow::Owned(value.to_string()),
+                )
+                .is_some()
+            {
+                return Err(invalid(&format!(
+                    "duplicate entry for {leading} leading and {total} total zero bytes"
+                )));
+            }
+        }
+
+        Ok(Reward { reward }.with_nibble_keys())
+    }
+
+    /// Derive the rewards for leading zero nibbles from those for leading zero
+    /// bytes: an even number of nibbles is worth as much as the same number of
+    /// whole bytes, and an odd number is valued in between the bytes on either
+    /// side (at their geometric mean, as rarity grows exponentially).
+    fn with_nibble_keys(mut self) -> Self {
+        let key = |leading: usize, total: usize, leading_nibbles: usize, granularity| {
+            let score = Score {
+                leading,
+                total,
+                zero_run: 0,
+                leading_nibbles,
+            };
+            score.reward_key(granularity)
+        };
+        for total in 0..=20 {
+            for leading in 0..=total {
+                let Some(value) = self.reward.get(&key(leading, total, 0, Granularity::Byte))
+                else {
+                    continue;
+                };
+                let next = self.get(&key(leading + 1, total, 0, Granularity::Byte));
+                let between = match (value.parse::<f64>(), next.map(str::parse::<f64>)) {
+                    (Ok(low), Some(Ok(high))) if leading < total => {
+                        Cow::Owned(format!("{:.0}", (low * high).sqrt()))
+                    }
+                    _ => value.clone(),
+                };
+
+                let value = value.clone();
+                let even = key(leading, total, 2 * leading, Granularity::Nibble);
+                let odd = key(leading, total, 2 * leading + 1, Granularity::Nibble);
+                self.reward.insert(even, value);
+                self.reward.insert(odd, between);
+            }
+        }
+        self
     }
 
     #[inline]
-    pub fn get(&self, value: &usize) -> Option<&'static str> {
-        self.reward.get(value).copied()
+    pub fn get(&self, value: &usize) -> Option<&str> {
+        self.reward.get(value).map(|reward| reward.as_ref())
+    }
+
+    /// The reward under `key` as a number, to compare rewards with. They can be
+    /// far larger than any integer type, so this is floating point.
+    pub fn value(&self, key: &usize) -> Option<f64> {
+        self.get(key)?.parse().ok()
+    }
+
+    /// Every shape of address, as `(leading, total)` zero bytes, whose reward
+    /// is at least `value`, ordered by leading and then total zero bytes.
+    pub fn combos_at_least(&self, value: f64) -> Vec<(usize, usize)> {
+        let mut combos = vec![];
+        for leading in 0..=20 {
+            for total in leading..=20 {
+                let score = Score {
+                    leading,
+                    total,
+                    zero_run: 0,
+                    leading_nibbles: 2 * leading,
+                };
+                let reward = self.value(&score.reward_key(Granularity::Byte));
+                if reward.map_or(false, |reward| reward >= value) {
+                    combos.push((leading, total));
+                }
+            }
+        }
+        combos
+    }
+
+    /// Describe an address with the given leading and total zero bytes in
+    /// words, e.g. `4 leading zero bytes (common)` or `6 total + 5 leading
+    /// (rare)`, rating how rare it is by its reward.
+    pub fn describe(&self, leading: usize, total: usize) -> String {
+        let score = Score {
+            leading,
+            total,
+            zero_run: 0,
+            leading_nibbles: 2 * leading,
+        };
+        let reward = self
+            .get(&score.reward_key(Granularity::Byte))
+            .and_then(|reward| reward.parse::<f64>().ok())
+            .unwrap_or(0.0);
+        let rarity = if reward < 1e2 {
+            "common"
+        } else if reward < 1e6 {
+            "uncommon"
+        } else if reward < 1e12 {
+            "rare"
+        } else {
+            "very rare"
+        };
+
+        if leading == total {
+            let bytes = if leading == 1 { "byte" } else { "bytes" };
+            format!("{leading} leading zero {bytes} ({rarity})")
+        } else {
+            format!("{total} total + {leading} leading ({rarity})")
+        }
+    }
+}
+
+/// Whether a reward from the table is at least `min_reward`. Rewards can be far
+/// larger than any integer type, so they are compared as floating point.
+pub(crate) fn reward_at_least(reward: &str, min_reward: u32) -> bool {
+    reward
+        .parse::<f64>()
+        .map_or(false, |reward| reward >= f64::from(min_reward))
+}
+
+impl Default for Reward {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn descriptions_name_the_zero_bytes_and_rarity() {
+        let rewards = Reward::new();
+        let cases = [
+            (1, 1, "1 leading zero byte (common)"),
+            (3, 3, "3 leading zero bytes (common)"),
+            (4, 6, "6 total + 4 leading (uncommon)"),
+            (5, 5, "5 leading zero bytes (uncommon)"),
+            (5, 6, "6 total + 5 leading (rare)"),
+            (6, 6, "6 leading zero bytes (rare)"),
+            (8, 8, "8 leading zero bytes (very rare)"),
+        ];
+        for (leading, total, description) in cases {
+            assert_eq!(rewards.describe(leading, total), description);
+        }
     }
 }
@@ -0,0 +1,93 @@
+use alloy_primitives::hex;
+use serde_json::{json, Value};
+use tiny_keccak::{Hasher, Keccak};
+
+/// Parse a function selector given either as 4 bytes of hex (`0x12345678`) or
+/// as a function signature (`initCodeHash()`), which is then hashed.
+pub(crate) fn parse_selector(selector: &str) -> Option<[u8; 4]> {
+    if selector.contains('(') {
+        let mut hash = [0; 32];
+        let mut keccak = Keccak::v256();
+        keccak.update(selector.trim().as_bytes());
+        keccak.finalize(&mut hash);
+        return hash[..4].try_into().ok();
+    }
+    let selector = selector.trim();
+    let selector = selector.strip_prefix("0x").unwrap_or(selector);
+    hex::decode(selector).ok()?.try_into().ok()
+}
+
+/// Call `selector` on the factory through the JSON-RPC node at `url`, and take
+/// the init code hash from what it returns: either the hash itself, as a
+/// `bytes32`, or the init code, as `bytes`, which is then hashed.
+pub(crate) fn fetch_init_code_hash(
+    url: &str,
+    factory: &[u8; 20],
+    selector: &[u8; 4],
+) -> Result<[u8; 32], String> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_call",
+        "params": [
+            {
+                "to": format!("0x{}", hex::encode(factory)),
+                "data": format!("0x{}", hex::encode(selector)),
+            },
+            "latest",
+        ],
+    });
+    let response = ureq::post(url)
+        .set("Content-Type", "application/json")
+        .send_string(&request.to_string())
+        .map_err(|e| format!("couldn't call the factory through {url}: {e}"))?
+        .into_string()
+        .map_err(|e| format!("couldn't read the answer from {url}: {e}"))?;
+    let response: Value = serde_json::from_str(&response)
+        .map_err(|e| format!("{url} didn't answer with JSON-RPC: {e}"))?;
+
+    if let Some(error) = response.get("error") {
+        let message = error["message"].as_str().unwrap_or("unknown error");
+        return Err(format!("the call to the factory failed: {message}"));
+    }
+    let result = response["result"]
+        .as_str()
+        .and_then(|result| hex::decode(result.trim_start_matches("0x")).ok())
+        .ok_or_else(|| format!("{url} didn't return the result of the call as hex"))?;
+
+    match result.len() {
+        32 => Ok(result.try_into().unwrap()),
+        _ => {
+            let init_code = abi_bytes(&result).ok_or_else(|| {
+                format!(
+                    "expected the factory to return a 32-byte init code hash or the init code, \
+                     but it returned {} bytes",
+                    result.len()
+                )
+            })?;
+            let mut hash = [0; 32];
+            let mut keccak = Keccak::v256();
+            keccak.update(init_code);
+            keccak.finalize(&mut hash);
+            Ok(hash)
+        }
+    }
+}
+
+/// Decode a single ABI-encoded `bytes` return value: the offset of the value,
+/// then its length, then the bytes themselves.
+fn abi_bytes(data: &[u8]) -> Option<&[u8]> {
+    let offset = abi_word(data, 0)?;
+    let len = abi_word(data, offset)?;
+    let start = offset.checked_add(32)?;
+    data.get(start..start.checked_add(len)?)
+}
+
+/// Read the 32-byte word at `at` as a length or offset, which must fit a `u64`.
+fn abi_word(data: &[u8], at: usize) -> Option<usize> {
+    let word = data.get(at..at.checked_add(32)?)?;
+    if word[..24].iter().any(|&b| b != 0) {
+        return None;
+    }
+    usize::try_from(u64::from_be_bytes(word[24..].try_into().unwrap())).ok()
+}
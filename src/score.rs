@@ -0,0 +1,255 @@
+use alloy_primitives::Address;
+
+/// Where the reward keys for leading zero nibbles start, after every key for
+/// leading zero bytes.
+const NIBBLE_KEYS: usize = 20 * 20 + 21;
+
+/// Whether leading zeroes are counted in whole bytes or in nibbles (half
+/// bytes), so that e.g. `0x0012...` has one leading zero byte but three
+/// leading zero nibbles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Granularity {
+    #[default]
+    Byte,
+    Nibble,
+}
+
+impl Granularity {
+    /// Parse a granularity as given on the command line.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "byte" => Some(Self::Byte),
+            "nibble" => Some(Self::Nibble),
+            _ => None,
+        }
+    }
+}
+
+/// Zero-byte statistics for a candidate address, shared by the CPU search and
+/// the host-side re-check of GPU solutions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Score {
+    /// Number of zero bytes at the start of the address.
+    pub leading: usize,
+    /// Number of zero bytes anywhere in the address.
+    pub total: usize,
+    /// Length of the longest run of consecutive zero bytes at any position.
+    pub zero_run: usize,
+    /// Number of zero nibbles at the start of the address.
+    pub leading_nibbles: usize,
+}
+
+impl Score {
+    /// The key used to look up the reward for this address in `Reward`, with
+    /// leading zeroes counted at the given granularity.
+    #[inline]
+    pub fn reward_key(&self, granularity: Granularity) -> usize {
+        match granularity {
+            Granularity::Byte => self.leading * 20 + self.total,
+            Granularity::Nibble => NIBBLE_KEYS + self.leading_nibbles * 21 + self.total,
+        }
+    }
+
+    /// The leading zeroes of this address counted at the given granularity.
+    #[inline]
+    pub fn leading_at(&self, granularity: Granularity) -> usize {
+        match granularity {
+            Granularity::Byte => self.leading,
+            Granularity::Nibble => self.leading_nibbles,
+        }
+    }
+
+    /// Whether this address is strictly rarer than `other`: it has more
+    /// leading zero bytes, or as many leading and more total zero bytes.
+    #[inline]
+    pub fn beats(&self, other: &Score) -> bool {
+        (self.leading, self.total) > (other.leading, other.total)
+    }
+}
+
+/// Repeated-byte statistics for a candidate address, for `--min-repeat`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RepeatScore {
+    /// Length in bytes of the longest run of one repeated byte, e.g. 3 for
+    /// `0x...777777...`.
+    pub byte_run: usize,
+    /// Length in bytes of the longest run of one repeated byte pair, e.g. 4
+    /// for `0x...12341234...`; a run of one repeated byte counts as well.
+    pub pair_run: usize,
+}
+
+impl RepeatScore {
+    /// The longer of the two runs.
+    #[inline]
+    pub fn longest(&self) -> usize {
+        self.byte_run.max(self.pair_run)
+    }
+}
+
+/// Find the longest runs of a repeated byte and of a repeated byte pair in an
+/// address.
+pub fn repeat_score(address: &[u8; 20]) -> RepeatScore {
+    let (mut byte_run, mut pair_run) = (1, 2);
+    let mut score = RepeatScore { byte_run, pair_run };
+    for i in 1..20 {
+        byte_run = if address[i] == address[i - 1] {
+            byte_run + 1
+        } else {
+            1
+        };
+        score.byte_run = score.byte_run.max(byte_run);
+        if i >= 2 {
+            pair_run = if address[i] == address[i - 2] {
+                pair_run + 1
+            } else {
+                2
+            };
+            score.pair_run = score.pair_run.max(pair_run);
+        }
+    }
+    score
+}
+
+/// Whether an address is numerically smaller than `bound`. Addresses are
+/// big-endian, so this is a plain lexicographic comparison of the bytes.
+#[inline]
+pub fn is_below(address: &[u8; 20], bound: &[u8; 20]) -> bool {
+    address < bound
+}
+
+/// Whether an address starts with the given hex nibbles (each `0..16`), so
+/// that e.g. `[1, 3, 3]` matches `0x1330...` through `0x133f...`.
+pub fn has_prefix(address: &[u8; 20], nibbles: &[u8]) -> bool {
+    nibbles.len() <= 40
+        && nibbles.iter().enumerate().all(|(i, &nibble)| match i % 2 {
+            0 => address[i / 2] >> 4 == nibble,
+            _ => address[i / 2] & 0x0f == nibble,
+        })
+}
+
+/// Whether an address contains the bytes of `pattern` starting at byte
+/// `offset`, e.g. `0xdeadbeef` at 4 in `0x12345678deadbeef...`.
+pub fn has_pattern(address: &[u8; 20], offset: usize, pattern: &[u8]) -> bool {
+    address.get(offset..offset + pattern.len()) == Some(pattern)
+}
+
+/// Whether an address has the given byte at each given position, e.g.
+/// `[(0, 0x00), (19, 0x42)]` in `0x00...42`, whatever its other bytes.
+pub fn has_mask(address: &[u8; 20], mask: &[(usize, u8)]) -> bool {
+    mask.iter()
+        .all(|&(position, byte)| address.get(position) == Some(&byte))
+}
+
+/// Whether an address contains any of the given runs of bytes anywhere, e.g.
+/// `0xdead` in `0x12dead...`.
+pub fn contains_any(address: &[u8; 20], patterns: &[Vec<u8>]) -> bool {
+    patterns.iter().any(|pattern| {
+        !pattern.is_empty() && address.windows(pattern.len()).any(|bytes| bytes == pattern)
+    })
+}
+
+/// Whether an address ends with the bytes of `suffix`, e.g. `0x0000` in
+/// `0x...12340000`.
+pub fn has_suffix(address: &[u8; 20], suffix: &[u8]) -> bool {
+    address.ends_with(suffix)
+}
+
+/// Whether `word` (made up of hex digits) appears with exactly the same casing
+/// in the EIP-55 checksummed form of an address, e.g. `DEAD` in
+/// `0x12DEAD...` but not in `0x12dEaD...`.
+pub fn has_checksum_word(address: &Address, word: &str) -> bool {
+    // rule out addresses that don't contain the word in any casing before
+    // paying for the checksum, which takes another keccak-256 hash
+    let nibble = |i: usize| match i % 2 {
+        0 => address[i / 2] >> 4,
+        _ => address[i / 2] & 0x0f,
+    };
+    let len = word.len();
+    let found = len <= 40
+        && (0..=40 - len).any(|start| {
+            word.chars()
+                .enumerate()
+                .all(|(i, c)| c.to_digit(16) == Some(nibble(start + i) as u32))
+        });
+
+    found && address.to_checksum(None)[2..].contains(word)
+}
+
+/// Count the leading, total, and longest consecutive zero bytes of an address,
+/// as well as its leading zero nibbles. An address of nothing but zero bytes
+/// has 20 leading zero bytes and 40 leading zero nibbles.
+pub fn score_address(address: &[u8; 20]) -> Score {
+    let mut total = 0;
+    let mut leading = None;
+    let mut leading_nibbles = 40;
+    let mut run = 0;
+    let mut zero_run = 0;
+    for (i, &b) in address.iter().enumerate() {
+        if b == 0 {
+            total += 1;
+            run += 1;
+            zero_run = zero_run.max(run);
+        } else {
+            run = 0;
+            if leading.is_none() {
+                // set leading on finding non-zero byte
+                leading = Some(i);
+                leading_nibbles = 2 * i + (b >> 4 == 0) as usize;
+            }
+        }
+    }
+
+    Score {
+        leading: leading.unwrap_or(20),
+        total,
+        zero_run,
+        leading_nibbles,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create2_address, Reward};
+    use alloy_primitives::hex;
+
+    #[test]
+    fn suffix_matches_the_last_bytes_of_a_known_address() {
+        // the first example of EIP-1014: a zero factory and salt, and the
+        // init code `0x00`
+        let init_code_hash =
+            hex!("bc36789e7a1e281436464229828f817d6612f7b477d66591ff96a9e064bcc98a");
+        let address = create2_address(&[0; 20], &[0; 32], &init_code_hash);
+        assert_eq!(address, hex!("4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38"));
+
+        assert!(has_suffix(&address, &hex!("38")));
+        assert!(has_suffix(&address, &hex!("0b26bf38")));
+        assert!(has_suffix(&address, &address[..]));
+        assert!(!has_suffix(&address, &hex!("0000")));
+        assert!(!has_suffix(&address, &hex!("26bf")));
+        assert!(!has_suffix(&address, &[0; 21]));
+    }
+
+    #[test]
+    fn an_all_zero_address_has_twenty_leading_zero_bytes() {
+        let score = score_address(&[0; 20]);
+        assert_eq!(
+            score,
+            Score {
+                leading: 20,
+                total: 20,
+                zero_run: 20,
+                leading_nibbles: 40,
+            }
+        );
+
+        let rewards = Reward::new();
+        let byte_reward = rewards.get(&score.reward_key(Granularity::Byte));
+        assert_eq!(
+            byte_reward,
+            Some("87112285931760246646623899502532662132736")
+        );
+        let nibble_reward = rewards.get(&score.reward_key(Granularity::Nibble));
+        assert_eq!(nibble_reward, byte_reward);
+    }
+}
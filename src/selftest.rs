@@ -0,0 +1,85 @@
+use alloy_primitives::{hex, Address};
+use std::fs;
+use std::path::Path;
+
+use crate::output::ResultWriter;
+use crate::{create2_address, score_address, Granularity, Preimage, Reward};
+
+// the built-in vector: the keyless CREATE2 factory, the null caller, and the
+// hash of empty init code, searched with an all-zero random segment
+const FACTORY: [u8; 20] = hex!("0000000000ffe8b47b3e2130213b802212439497");
+const CALLER: [u8; 20] = [0; 20];
+const INIT_CODE_HASH: [u8; 32] =
+    hex!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+
+// the first nonce giving two leading zero bytes, and the resulting address
+const EXPECTED_NONCE: u64 = 206712;
+const EXPECTED_ADDRESS: [u8; 20] = hex!("0000cce7e429e6c198294bb1e196f2d5ad6e3cdc");
+
+/// Run a small deterministic CPU search against a built-in vector and check
+/// that it finds the expected solution, that the solution verifies, and that it
+/// survives a round trip through a results file.
+pub fn selftest() -> Result<(), String> {
+    // search the nonces in order, exactly as `cpu` hashes them
+    let mut salt_head = [0; Preimage::SALT_HEAD_LEN];
+    salt_head[..20].copy_from_slice(&CALLER);
+    let hasher = Preimage::new(&FACTORY, &INIT_CODE_HASH).hasher(&salt_head);
+
+    let found = (0..1 << 20).find_map(|nonce: u64| {
+        let address = hasher.address(&nonce.to_le_bytes()[..6]);
+        (score_address(&address).leading >= 2).then_some((nonce, address))
+    });
+    let Some((nonce, address)) = found else {
+        return Err("the search didn't find a solution".to_string());
+    };
+    if nonce != EXPECTED_NONCE || address != Address::from(EXPECTED_ADDRESS) {
+        return Err(format!("the search found {address} at nonce {nonce}"));
+    }
+
+    // the salt must independently hash to the same address
+    let mut salt = [0; 32];
+    salt[..20].copy_from_slice(&CALLER);
+    salt[26..].copy_from_slice(&nonce.to_le_bytes()[..6]);
+    let verified = create2_address(&FACTORY, &salt, &INIT_CODE_HASH);
+    if verified != address {
+        return Err(format!(
+            "the salt verifies to {verified} instead of {address}"
+        ));
+    }
+
+    // write the solution in the usual format, then read it back and re-verify
+    let score = score_address(&address);
+    let rewards = Reward::new();
+    let reward = rewards
+        .get(&score.reward_key(Granularity::Byte))
+        .unwrap_or("0");
+    let line = format!("0x{} => {address} => {reward}", hex::encode(salt));
+    let path =
+        std::env::temp_dir().join(format!("create2crunch-selftest-{}.txt", std::process::id()));
+    let result = round_trip(&path, score.leading, &line);
+    let _ = fs::remove_file(&path);
+    let read_back = result?;
+    if read_back != line {
+        return Err(format!(
+            "the results file contained `{read_back}` instead of `{line}`"
+        ));
+    }
+    let salt = read_back.split(" => ").next().unwrap_or_default();
+    let Ok(Ok(salt)) = hex::decode(salt).map(<[u8; 32]>::try_from) else {
+        return Err(format!("couldn't parse the salt from `{read_back}`"));
+    };
+    if create2_address(&FACTORY, &salt, &INIT_CODE_HASH) != address {
+        return Err("the salt read back from the results file doesn't verify".to_string());
+    }
+
+    Ok(())
+}
+
+fn round_trip(path: &Path, leading: usize, line: &str) -> Result<String, String> {
+    ResultWriter::new(path, false, None, None)
+        .map_err(|e| e.to_string())?
+        .write(leading, line);
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("couldn't read back results: {e}"))?;
+    Ok(contents.lines().last().unwrap_or_default().to_string())
+}
@@ -0,0 +1,41 @@
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+/// The stop flags of the searches currently running, which Ctrl-C and
+/// [`request_stop`] set. A search that has ended no longer holds its flag.
+static RUNNING: Mutex<Vec<Weak<AtomicBool>>> = Mutex::new(Vec::new());
+
+/// A stop flag for a search about to start, set by Ctrl-C and
+/// [`request_stop`] for as long as the search holds on to it.
+pub(crate) fn stop_flag() -> Arc<AtomicBool> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut running = RUNNING.lock().unwrap();
+    running.retain(|stop| stop.strong_count() > 0);
+    running.push(Arc::downgrade(&stop));
+    stop
+}
+
+/// Ask every search that is running to stop after its current batch of work,
+/// write out what it has found so far, and return. Searches started later
+/// aren't affected.
+pub fn request_stop() {
+    let running = RUNNING.lock().unwrap();
+    for stop in running.iter().filter_map(Weak::upgrade) {
+        stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Call [`request_stop`] on Ctrl-C. A second Ctrl-C exits right away, in case
+/// the current batch of work takes too long to finish.
+pub fn stop_on_ctrl_c() -> Result<(), String> {
+    let pressed = AtomicBool::new(false);
+    ctrlc::set_handler(move || {
+        if pressed.swap(true, Ordering::Relaxed) {
+            process::exit(130);
+        }
+        request_stop();
+        eprintln!("Stopping after the current batch of work (press Ctrl-C again to exit now)...");
+    })
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,34 @@
+use serde::Serialize;
+use std::io;
+use std::path::Path;
+
+use crate::output::write_atomically;
+
+/// A snapshot of a GPU search, as written with `--status-file` for external
+/// monitoring.
+#[derive(Serialize)]
+pub(crate) struct Status {
+    pub(crate) state: State,
+    pub(crate) runtime_secs: f64,
+    pub(crate) cumulative_nonce: u64,
+    pub(crate) hashes_per_sec: f64,
+    pub(crate) found: u64,
+}
+
+/// Whether the search is still going, so that a reader can tell a paused or
+/// finished search from one that has hung.
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum State {
+    Running,
+    Paused,
+    Finished,
+}
+
+impl Status {
+    /// Write the status to `path` as JSON, replacing the previous one.
+    pub(crate) fn save(&self, path: &Path) -> io::Result<()> {
+        let contents = serde_json::to_string(self).expect("statuses are always serializable");
+        write_atomically(path, contents.as_bytes())
+    }
+}